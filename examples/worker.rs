@@ -0,0 +1,21 @@
+extern crate libnotify;
+
+use std::thread;
+
+fn main() {
+    // Init libnotify
+    libnotify::init("myapp").unwrap();
+    // Create a channel to request notifications from other threads
+    let (sender, receiver) = libnotify::notification_channel();
+    // Request a notification from a worker thread
+    let worker = thread::spawn(move || {
+        let mut spec = libnotify::NotificationSpec::new("Work done");
+        spec.body = Some(String::from("The worker thread has finished"));
+        sender.send(spec).unwrap();
+    });
+    worker.join().unwrap();
+    // Show the requested notifications on the main thread
+    receiver.show_pending().unwrap();
+    // We are done, deinit
+    libnotify::uninit();
+}
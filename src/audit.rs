@@ -0,0 +1,129 @@
+use Notification;
+use Urgency;
+use functions::lock;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+
+static AUDIT_LOG: Mutex<Option<File>> = Mutex::new(None);
+
+
+/// Appends a line to the file at `path` for every notification successfully
+/// shown in this process from now on, replacing a previously enabled log.
+/// The file is created if it does not exist.
+///
+/// Each line is a JSON object with the following fields:
+///
+/// * `timestamp`: the time shown, in milliseconds since the Unix epoch
+/// * `id`: the id assigned by the server
+/// * `summary`: the summary text
+/// * `body`: the body text, or `null`
+/// * `urgency`: `"low"`, `"normal"` or `"critical"`, or `null` if none was
+///   set through this crate
+///
+/// Each line is written with a single unbuffered write. Errors writing to
+/// the log are ignored, so that they never make showing a notification
+/// fail.
+///
+/// Only available with the `audit-log` feature.
+/// ## `path`
+/// The log file.
+///
+/// # Returns
+///
+/// `Ok(())` on success, or `Err(err)` if the file cannot be opened
+pub fn enable_audit_log(path: PathBuf) -> io::Result<()> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    *lock(&AUDIT_LOG) = Some(file);
+    Ok(())
+}
+
+/// Stops writing the log enabled with `enable_audit_log`, if any.
+///
+/// Only available with the `audit-log` feature.
+pub fn disable_audit_log() {
+    *lock(&AUDIT_LOG) = None;
+}
+
+pub(crate) fn log_shown(
+    notification: &Notification,
+    summary: &str,
+    body: Option<&str>,
+) {
+    let mut log = lock(&AUDIT_LOG);
+    let file = match *log {
+        Some(ref mut file) => file,
+        None => return,
+    };
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_millis());
+    let urgency = match notification.cached_urgency() {
+        Some(Urgency::Low) => "\"low\"",
+        Some(Urgency::Normal) => "\"normal\"",
+        Some(Urgency::Critical) => "\"critical\"",
+        _ => "null",
+    };
+    let line = format!(
+        "{{\"timestamp\":{},\"id\":{},\"summary\":{},\"body\":{},\
+         \"urgency\":{}}}\n",
+        timestamp,
+        notification.get_id(),
+        json_string(summary),
+        body.map_or(String::from("null"), json_string),
+        urgency
+    );
+    let _ = file.write_all(line.as_bytes());
+}
+
+fn json_string(s: &str) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                json.push_str(&format!("\\u{:04x}", c as u32))
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_string_escapes_quotes_and_backslashes() {
+        assert_eq!(json_string(""), "\"\"");
+        assert_eq!(json_string("say \"hi\""), "\"say \\\"hi\\\"\"");
+        assert_eq!(json_string("C:\\dir\\"), "\"C:\\\\dir\\\\\"");
+    }
+
+    #[test]
+    fn json_string_escapes_control_characters() {
+        assert_eq!(json_string("a\nb\rc\td"), "\"a\\nb\\rc\\td\"");
+        assert_eq!(
+            json_string("\u{0}\u{1b}\u{1f}"),
+            "\"\\u0000\\u001b\\u001f\""
+        );
+        assert_eq!(json_string("\u{7f}"), "\"\u{7f}\"");
+    }
+
+    #[test]
+    fn json_string_keeps_non_ascii() {
+        assert_eq!(json_string("héllo 日本 🎉"), "\"héllo 日本 🎉\"");
+    }
+}
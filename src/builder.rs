@@ -0,0 +1,161 @@
+use Notification;
+use NotificationSpec;
+use Timeout;
+use UnsupportedFeature;
+use Urgency;
+use ValidationIssue;
+use glib;
+
+
+/// A problem found by `NotificationBuilder::build_checked`.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum BuildError {
+    /// The summary is empty or consists only of whitespace.
+    EmptySummary,
+    /// The named field contains a nul byte and cannot be sent.
+    NulByte(&'static str),
+    /// The server lacks a capability needed to display the notification as
+    /// described, e.g. `body` or `body-markup`.
+    Unsupported(UnsupportedFeature),
+}
+
+
+/// A fluent builder for `Notification`s.
+///
+/// ```no_run
+/// extern crate libnotify;
+///
+/// fn main() {
+///     libnotify::init("myapp").unwrap();
+///     let n = libnotify::NotificationBuilder::new("Summary")
+///         .body("Body")
+///         .urgency(libnotify::Urgency::Critical)
+///         .build()
+///         .unwrap();
+///     n.show().unwrap();
+///     libnotify::uninit();
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct NotificationBuilder {
+    spec: NotificationSpec,
+}
+
+impl NotificationBuilder {
+    /// Creates a new `NotificationBuilder` with the given summary and
+    /// everything else unset.
+    /// ## `summary`
+    /// The required summary text.
+    ///
+    /// # Returns
+    ///
+    /// The new `NotificationBuilder`.
+    pub fn new(summary: &str) -> NotificationBuilder {
+        NotificationBuilder {
+            spec: NotificationSpec::new(summary),
+        }
+    }
+
+    /// Sets the summary text.
+    pub fn summary(mut self, summary: &str) -> NotificationBuilder {
+        self.spec.summary = summary.to_owned();
+        self
+    }
+
+    /// Sets the body text.
+    pub fn body(mut self, body: &str) -> NotificationBuilder {
+        self.spec.body = Some(body.to_owned());
+        self
+    }
+
+    /// Sets the icon theme icon name or filename.
+    pub fn icon(mut self, icon: &str) -> NotificationBuilder {
+        self.spec.icon = Some(icon.to_owned());
+        self
+    }
+
+    /// Sets the urgency level.
+    pub fn urgency(mut self, urgency: Urgency) -> NotificationBuilder {
+        self.spec.urgency = Some(urgency);
+        self
+    }
+
+    /// Sets the timeout, either a `Timeout` or plain milliseconds. See also
+    /// the `NotificationBuilder::never` and
+    /// `NotificationBuilder::default_timeout` shortcuts.
+    pub fn timeout<T: Into<Timeout>>(
+        mut self,
+        timeout: T,
+    ) -> NotificationBuilder {
+        self.spec.timeout = Some(timeout.into().into());
+        self
+    }
+
+    /// Never expires the notification, same as `timeout(Timeout::Never)`.
+    pub fn never(self) -> NotificationBuilder {
+        self.timeout(Timeout::Never)
+    }
+
+    /// Leaves the expiration to the server, same as
+    /// `timeout(Timeout::Default)`.
+    pub fn default_timeout(self) -> NotificationBuilder {
+        self.timeout(Timeout::Default)
+    }
+
+    /// Sets the category.
+    pub fn category(mut self, category: &str) -> NotificationBuilder {
+        self.spec.category = Some(category.to_owned());
+        self
+    }
+
+    /// Adds a hint, replacing an earlier one for the same `key`.
+    pub fn hint(
+        mut self,
+        key: &str,
+        value: glib::Variant,
+    ) -> NotificationBuilder {
+        self.spec.hints.retain(|(k, _)| k != key);
+        self.spec.hints.push((key.to_owned(), value));
+        self
+    }
+
+    /// Creates the `Notification`, without showing it.
+    ///
+    /// # Returns
+    ///
+    /// The new `Notification`, or `Err(str)` for the first string that
+    /// contains a nul byte
+    pub fn build(&self) -> Result<Notification, String> {
+        self.spec.build()
+    }
+
+    /// Like `NotificationBuilder::build`, but first checks everything that
+    /// could make the notification fail or display differently than
+    /// described, and reports all problems at once. This includes an empty
+    /// summary and missing server capabilities, which `build` accepts.
+    ///
+    /// # Returns
+    ///
+    /// The new `Notification`, or `Err(errors)` listing all problems
+    pub fn build_checked(&self) -> Result<Notification, Vec<BuildError>> {
+        if let Err(issues) = self.spec.validate() {
+            return Err(issues.into_iter().map(BuildError::from).collect());
+        }
+        Ok(self.build().expect("NotificationBuilder::build_checked checks it"))
+    }
+}
+
+impl From<ValidationIssue> for BuildError {
+    fn from(issue: ValidationIssue) -> BuildError {
+        match issue {
+            ValidationIssue::EmptySummary => BuildError::EmptySummary,
+            ValidationIssue::NulByte(field) => BuildError::NulByte(field),
+            ValidationIssue::BodyNotSupported => {
+                BuildError::Unsupported(UnsupportedFeature::new("body"))
+            }
+            ValidationIssue::MarkupNotSupported => {
+                BuildError::Unsupported(UnsupportedFeature::new("body-markup"))
+            }
+        }
+    }
+}
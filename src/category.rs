@@ -0,0 +1,302 @@
+use Urgency;
+use std::convert::Infallible;
+use std::fmt;
+use std::str::FromStr;
+
+
+/// The standard notification categories of the freedesktop notification
+/// specification, which servers may use to filter or display notifications
+/// in a certain way. Categories round-trip through their string
+/// representation with `Display` and `FromStr`, where strings that are not
+/// a standard category become `Category::Custom`.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Category {
+    /// A generic device-related notification.
+    Device,
+    /// A device, such as a USB device, was added to the system.
+    DeviceAdded,
+    /// A device had some kind of error.
+    DeviceError,
+    /// A device, such as a USB device, was removed from the system.
+    DeviceRemoved,
+    /// A generic e-mail-related notification.
+    Email,
+    /// A new e-mail notification.
+    EmailArrived,
+    /// A notification stating that an e-mail has bounced.
+    EmailBounced,
+    /// A generic instant message-related notification.
+    Im,
+    /// An instant message error notification.
+    ImError,
+    /// A received instant message notification.
+    ImReceived,
+    /// A generic network notification.
+    Network,
+    /// A network connection notification, such as successful sign-on to a
+    /// network service.
+    NetworkConnected,
+    /// A network disconnected notification.
+    NetworkDisconnected,
+    /// A network-related or connection-related error.
+    NetworkError,
+    /// A generic presence change notification.
+    Presence,
+    /// An offline presence change notification.
+    PresenceOffline,
+    /// An online presence change notification.
+    PresenceOnline,
+    /// A generic file transfer or download notification.
+    Transfer,
+    /// A file transfer or download complete notification.
+    TransferComplete,
+    /// A file transfer or download error.
+    TransferError,
+    /// A category not defined by the specification, e.g. a vendor-specific
+    /// `x-vendor.class` category.
+    Custom(String),
+}
+
+impl Category {
+    /// Gets the category string, as sent to the notification server.
+    ///
+    /// # Returns
+    ///
+    /// The category string, e.g. `"email.arrived"`.
+    pub fn as_str(&self) -> &str {
+        match *self {
+            Category::Device => "device",
+            Category::DeviceAdded => "device.added",
+            Category::DeviceError => "device.error",
+            Category::DeviceRemoved => "device.removed",
+            Category::Email => "email",
+            Category::EmailArrived => "email.arrived",
+            Category::EmailBounced => "email.bounced",
+            Category::Im => "im",
+            Category::ImError => "im.error",
+            Category::ImReceived => "im.received",
+            Category::Network => "network",
+            Category::NetworkConnected => "network.connected",
+            Category::NetworkDisconnected => "network.disconnected",
+            Category::NetworkError => "network.error",
+            Category::Presence => "presence",
+            Category::PresenceOffline => "presence.offline",
+            Category::PresenceOnline => "presence.online",
+            Category::Transfer => "transfer",
+            Category::TransferComplete => "transfer.complete",
+            Category::TransferError => "transfer.error",
+            Category::Custom(ref category) => category,
+        }
+    }
+
+    /// Gets the urgency conventionally used for notifications of this
+    /// category:
+    ///
+    /// * `Urgency::Critical` for the error categories (`device.error`,
+    ///   `im.error`, `network.error` and `transfer.error`) and
+    ///   `email.bounced`
+    /// * `Urgency::Low` for the device hotplug, network connection and
+    ///   presence categories
+    /// * `Urgency::Normal` for all other categories
+    ///
+    /// # Returns
+    ///
+    /// The default urgency.
+    pub fn default_urgency(&self) -> Urgency {
+        match *self {
+            Category::DeviceError |
+            Category::EmailBounced |
+            Category::ImError |
+            Category::NetworkError |
+            Category::TransferError => Urgency::Critical,
+            Category::DeviceAdded |
+            Category::DeviceRemoved |
+            Category::NetworkConnected |
+            Category::NetworkDisconnected |
+            Category::Presence |
+            Category::PresenceOffline |
+            Category::PresenceOnline => Urgency::Low,
+            _ => Urgency::Normal,
+        }
+    }
+
+    /// Gets a theme icon from the freedesktop icon naming specification
+    /// that suits notifications of this category:
+    ///
+    /// * `dialog-error`: `device.error`, `im.error`, `transfer.error`
+    /// * `dialog-warning`: `email.bounced`
+    /// * `drive-removable-media`: `device`, `device.added`, `device.removed`
+    /// * `emblem-default`: `transfer.complete`
+    /// * `mail-message-new`: `email.arrived`, `im.received`
+    /// * `mail-unread`: `email`, `im`
+    /// * `network-error`: `network.error`
+    /// * `network-idle`: `network`, `network.connected`
+    /// * `network-offline`: `network.disconnected`
+    /// * `network-transmit-receive`: `transfer`
+    /// * `user-available`: `presence`, `presence.online`
+    /// * `user-offline`: `presence.offline`
+    ///
+    /// # Returns
+    ///
+    /// The icon name, or `None` for custom categories.
+    pub fn default_icon(&self) -> Option<&'static str> {
+        Some(match *self {
+            Category::DeviceError |
+            Category::ImError |
+            Category::TransferError => "dialog-error",
+            Category::EmailBounced => "dialog-warning",
+            Category::Device |
+            Category::DeviceAdded |
+            Category::DeviceRemoved => "drive-removable-media",
+            Category::TransferComplete => "emblem-default",
+            Category::EmailArrived |
+            Category::ImReceived => "mail-message-new",
+            Category::Email |
+            Category::Im => "mail-unread",
+            Category::NetworkError => "network-error",
+            Category::Network |
+            Category::NetworkConnected => "network-idle",
+            Category::NetworkDisconnected => "network-offline",
+            Category::Transfer => "network-transmit-receive",
+            Category::Presence |
+            Category::PresenceOnline => "user-available",
+            Category::PresenceOffline => "user-offline",
+            Category::Custom(_) => return None,
+        })
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Category {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Category, Infallible> {
+        Ok(match s {
+            "device" => Category::Device,
+            "device.added" => Category::DeviceAdded,
+            "device.error" => Category::DeviceError,
+            "device.removed" => Category::DeviceRemoved,
+            "email" => Category::Email,
+            "email.arrived" => Category::EmailArrived,
+            "email.bounced" => Category::EmailBounced,
+            "im" => Category::Im,
+            "im.error" => Category::ImError,
+            "im.received" => Category::ImReceived,
+            "network" => Category::Network,
+            "network.connected" => Category::NetworkConnected,
+            "network.disconnected" => Category::NetworkDisconnected,
+            "network.error" => Category::NetworkError,
+            "presence" => Category::Presence,
+            "presence.offline" => Category::PresenceOffline,
+            "presence.online" => Category::PresenceOnline,
+            "transfer" => Category::Transfer,
+            "transfer.complete" => Category::TransferComplete,
+            "transfer.error" => Category::TransferError,
+            category => Category::Custom(category.to_owned()),
+        })
+    }
+}
+
+impl<'a> From<&'a str> for Category {
+    fn from(category: &'a str) -> Category {
+        match category.parse() {
+            Ok(category) => category,
+            Err(never) => match never {},
+        }
+    }
+}
+
+impl From<String> for Category {
+    fn from(category: String) -> Category {
+        Category::from(category.as_str())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STANDARD: &[Category] = &[
+        Category::Device,
+        Category::DeviceAdded,
+        Category::DeviceError,
+        Category::DeviceRemoved,
+        Category::Email,
+        Category::EmailArrived,
+        Category::EmailBounced,
+        Category::Im,
+        Category::ImError,
+        Category::ImReceived,
+        Category::Network,
+        Category::NetworkConnected,
+        Category::NetworkDisconnected,
+        Category::NetworkError,
+        Category::Presence,
+        Category::PresenceOffline,
+        Category::PresenceOnline,
+        Category::Transfer,
+        Category::TransferComplete,
+        Category::TransferError,
+    ];
+
+    #[test]
+    fn standard_categories_round_trip() {
+        for category in STANDARD {
+            let parsed: Category = category.to_string().parse().unwrap();
+            assert_eq!(parsed, *category);
+        }
+    }
+
+    #[test]
+    fn standard_categories_are_distinct() {
+        for (i, category) in STANDARD.iter().enumerate() {
+            for other in &STANDARD[i + 1..] {
+                assert_ne!(category.as_str(), other.as_str());
+            }
+        }
+    }
+
+    #[test]
+    fn default_icons() {
+        let icons = [
+            ("device", "drive-removable-media"),
+            ("device.added", "drive-removable-media"),
+            ("device.error", "dialog-error"),
+            ("device.removed", "drive-removable-media"),
+            ("email", "mail-unread"),
+            ("email.arrived", "mail-message-new"),
+            ("email.bounced", "dialog-warning"),
+            ("im", "mail-unread"),
+            ("im.error", "dialog-error"),
+            ("im.received", "mail-message-new"),
+            ("network", "network-idle"),
+            ("network.connected", "network-idle"),
+            ("network.disconnected", "network-offline"),
+            ("network.error", "network-error"),
+            ("presence", "user-available"),
+            ("presence.offline", "user-offline"),
+            ("presence.online", "user-available"),
+            ("transfer", "network-transmit-receive"),
+            ("transfer.complete", "emblem-default"),
+            ("transfer.error", "dialog-error"),
+        ];
+        assert_eq!(icons.len(), STANDARD.len());
+        for &(category, icon) in &icons {
+            assert_eq!(Category::from(category).default_icon(), Some(icon));
+        }
+        assert_eq!(Category::from("x-vendor.class").default_icon(), None);
+    }
+
+    #[test]
+    fn custom_categories_round_trip() {
+        let category = Category::from("x-vendor.class");
+        assert_eq!(category, Category::Custom("x-vendor.class".to_owned()));
+        assert_eq!(category.to_string(), "x-vendor.class");
+    }
+}
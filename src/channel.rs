@@ -0,0 +1,87 @@
+use NotificationSpec;
+use glib;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::sync::mpsc;
+
+
+/// Creates a channel for requesting notifications from any thread.
+///
+/// libnotify must only be used from the thread that owns the main context,
+/// so worker threads cannot show notifications themselves. Instead, they
+/// send a `NotificationSpec` through the `NotificationSender`, which may be
+/// cloned and moved to other threads, and the owning thread regularly calls
+/// `NotificationReceiver::show_pending` to show them.
+///
+/// # Returns
+///
+/// The sending and the receiving half of the channel.
+pub fn notification_channel() -> (NotificationSender, NotificationReceiver) {
+    let (sender, receiver) = mpsc::channel();
+    let receiver = NotificationReceiver {
+        receiver,
+        pending: RefCell::new(VecDeque::new()),
+    };
+    (NotificationSender(sender), receiver)
+}
+
+
+/// The sending half of a `notification_channel`.
+#[derive(Clone, Debug)]
+pub struct NotificationSender(mpsc::Sender<NotificationSpec>);
+
+impl NotificationSender {
+    /// Requests a notification to be shown by the receiving thread.
+    /// ## `spec`
+    /// The notification to show.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or `Err(str)` if a string in `spec` contains a
+    /// nul byte or the `NotificationReceiver` has been dropped
+    pub fn send(&self, spec: NotificationSpec) -> Result<(), String> {
+        spec.check()?;
+        self.0.send(spec).map_err(|_| {
+            String::from("Notification receiver disconnected")
+        })
+    }
+}
+
+
+/// The receiving half of a `notification_channel`.
+#[derive(Debug)]
+pub struct NotificationReceiver {
+    receiver: mpsc::Receiver<NotificationSpec>,
+    // Received requests not shown yet, starting with one that failed.
+    pending: RefCell<VecDeque<NotificationSpec>>,
+}
+
+impl NotificationReceiver {
+    /// Shows all notifications requested so far, without blocking. This must
+    /// be called on the thread using libnotify, e.g. from a
+    /// `glib::timeout_add` callback in the main loop.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(count)` with the number of notifications shown, or the first
+    /// `Err(err)` on error, in which case the failed request and the
+    /// remaining ones stay queued, and the next call starts with the failed
+    /// request
+    pub fn show_pending(&self) -> Result<usize, glib::error::Error> {
+        let mut pending = self.pending.borrow_mut();
+        pending.extend(self.receiver.try_iter());
+        let mut count = 0;
+        while let Some(spec) = pending.pop_front() {
+            let shown = spec
+                .build()
+                .expect("NotificationSender::send checks the spec")
+                .show();
+            if let Err(err) = shown {
+                pending.push_front(spec);
+                return Err(err);
+            }
+            count += 1;
+        }
+        Ok(count)
+    }
+}
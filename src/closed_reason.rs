@@ -0,0 +1,31 @@
+use glib::translate::*;
+
+
+/// The reason a notification was closed, as reported by the notification
+/// server.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum ClosedReason {
+    /// The notification expired.
+    Expired,
+    /// The notification was dismissed by the user.
+    Dismissed,
+    /// The notification was closed by a call to `Notification::close`.
+    CloseNotification,
+    /// The server did not give a reason.
+    Undefined,
+    #[doc(hidden)]
+    __Unknown(i32),
+}
+
+#[doc(hidden)]
+impl FromGlib<i32> for ClosedReason {
+    fn from_glib(value: i32) -> Self {
+        match value {
+            1 => ClosedReason::Expired,
+            2 => ClosedReason::Dismissed,
+            3 => ClosedReason::CloseNotification,
+            4 => ClosedReason::Undefined,
+            value => ClosedReason::__Unknown(value),
+        }
+    }
+}
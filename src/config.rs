@@ -0,0 +1,23 @@
+use Timeout;
+use Urgency;
+use glib;
+
+
+/// The content and presentation of a notification, applied all at once with
+/// `Notification::configure`. A default `NotificationConfig` has an empty
+/// summary and leaves everything else unset.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NotificationConfig {
+    /// The required summary text.
+    pub summary: String,
+    /// The optional body text.
+    pub body: Option<String>,
+    /// The optional icon theme icon name or filename.
+    pub icon: Option<String>,
+    /// The urgency level, or `None` to keep the current one.
+    pub urgency: Option<Urgency>,
+    /// The timeout, or `None` to keep the current one.
+    pub timeout: Option<Timeout>,
+    /// Hints set in addition to the current ones, in order.
+    pub hints: Vec<(String, glib::Variant)>,
+}
@@ -0,0 +1,223 @@
+use get_server_info;
+use glib;
+use glib::translate::*;
+use glib_ffi;
+use std::error;
+use std::fmt;
+
+
+/// `G_DBUS_ERROR_SERVICE_UNKNOWN` in the `g-dbus-error-quark` domain.
+const DBUS_ERROR_SERVICE_UNKNOWN: i32 = 2;
+/// `G_DBUS_ERROR_TIMEOUT` in the `g-dbus-error-quark` domain.
+const DBUS_ERROR_TIMEOUT: i32 = 12;
+/// `G_DBUS_ERROR_TIMED_OUT` in the `g-dbus-error-quark` domain.
+const DBUS_ERROR_TIMED_OUT: i32 = 20;
+/// `G_IO_ERROR_TIMED_OUT` in the `g-io-error-quark` domain.
+const IO_ERROR_TIMED_OUT: i32 = 24;
+/// `G_IO_ERROR_INVALID_ARGUMENT` in the `g-io-error-quark` domain.
+const IO_ERROR_INVALID_ARGUMENT: i32 = 13;
+/// The code of rate limited shows in this crate's error domain.
+const RATE_LIMITED: i32 = 1;
+
+
+/// The error returned by methods that require a capability the running
+/// notification server does not advertise, such as
+/// `Notification::try_add_action`.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct UnsupportedFeature {
+    /// The missing capability, as listed by `get_server_caps()`.
+    pub feature: String,
+    /// The name of the server, as reported by `get_server_info()`, or an
+    /// empty string if it could not be queried.
+    pub server: String,
+}
+
+impl UnsupportedFeature {
+    /// Creates the error for the capability `feature` of the running server.
+    pub(crate) fn new(feature: &str) -> UnsupportedFeature {
+        UnsupportedFeature {
+            feature: feature.to_owned(),
+            server: get_server_info()
+                .map(|(name, _, _, _)| name)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+
+impl fmt::Display for UnsupportedFeature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.server.is_empty() {
+            write!(f, "server does not support feature '{}'", self.feature)
+        } else {
+            write!(
+                f,
+                "server '{}' does not support feature '{}'",
+                self.server,
+                self.feature
+            )
+        }
+    }
+}
+
+impl error::Error for UnsupportedFeature {}
+
+
+/// The error returned by `Notification::try_add_action` and
+/// `Notification::add_inline_reply`.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ActionError {
+    /// The named argument contains a nul byte and cannot be sent.
+    NulByte(&'static str),
+    /// The server does not advertise the required capability.
+    Unsupported(UnsupportedFeature),
+    /// There is no session bus to receive the server's signals on.
+    NoSessionBus,
+}
+
+impl From<UnsupportedFeature> for ActionError {
+    fn from(err: UnsupportedFeature) -> ActionError {
+        ActionError::Unsupported(err)
+    }
+}
+
+impl fmt::Display for ActionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ActionError::NulByte(what) => write!(f, "Nul byte in {}", what),
+            ActionError::Unsupported(ref err) => err.fmt(f),
+            ActionError::NoSessionBus => write!(f, "no session bus"),
+        }
+    }
+}
+
+impl error::Error for ActionError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            ActionError::Unsupported(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+
+/// Helpers for inspecting the `glib::error::Error` returned by
+/// `Notification::show` and `Notification::close`.
+pub trait NotificationErrorExt {
+    /// Gets the raw error code of the underlying `GError`.
+    ///
+    /// # Returns
+    ///
+    /// The error code, which is only meaningful together with its domain.
+    fn code(&self) -> i32;
+
+    /// Whether the call timed out while waiting for the notification server.
+    ///
+    /// This matches `G_DBUS_ERROR_TIMEOUT` (12) and `G_DBUS_ERROR_TIMED_OUT`
+    /// (20) in the D-Bus error domain, as well as `G_IO_ERROR_TIMED_OUT` (24)
+    /// in the GIO error domain, which is what GDBus reports when a method
+    /// call receives no reply in time.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the error is a timeout, `false` otherwise.
+    fn is_timeout(&self) -> bool;
+
+    /// Whether no notification server is running on the session bus.
+    ///
+    /// This matches `G_DBUS_ERROR_SERVICE_UNKNOWN` (2) in the D-Bus error
+    /// domain, which corresponds to `org.freedesktop.DBus.Error.ServiceUnknown`.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the notification service is unknown, `false` otherwise.
+    fn is_service_unknown(&self) -> bool;
+
+    /// Whether `Notification::show` dropped the notification because of the
+    /// limit set with `set_rate_limit()`, without contacting the server.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the notification was rate limited, `false` otherwise.
+    fn is_rate_limited(&self) -> bool;
+}
+
+impl NotificationErrorExt for glib::error::Error {
+    fn code(&self) -> i32 {
+        let ptr: *const glib_ffi::GError = self.to_glib_none().0;
+        unsafe { (*ptr).code }
+    }
+
+    fn is_timeout(&self) -> bool {
+        let ptr: *const glib_ffi::GError = self.to_glib_none().0;
+        let (domain, code) = unsafe { ((*ptr).domain, (*ptr).code) };
+
+        (domain == dbus_error_quark() &&
+             (code == DBUS_ERROR_TIMEOUT || code == DBUS_ERROR_TIMED_OUT)) ||
+            (domain == io_error_quark() && code == IO_ERROR_TIMED_OUT)
+    }
+
+    fn is_service_unknown(&self) -> bool {
+        let ptr: *const glib_ffi::GError = self.to_glib_none().0;
+        let (domain, code) = unsafe { ((*ptr).domain, (*ptr).code) };
+
+        domain == dbus_error_quark() && code == DBUS_ERROR_SERVICE_UNKNOWN
+    }
+
+    fn is_rate_limited(&self) -> bool {
+        let ptr: *const glib_ffi::GError = self.to_glib_none().0;
+        let (domain, code) = unsafe { ((*ptr).domain, (*ptr).code) };
+
+        domain == libnotify_error_quark() && code == RATE_LIMITED
+    }
+}
+
+
+/// Creates a `G_IO_ERROR_INVALID_ARGUMENT` error for methods returning
+/// `glib::error::Error` that reject an argument before contacting the
+/// server, e.g. one with a nul byte.
+pub(crate) fn invalid_argument(message: &str) -> glib::error::Error {
+    unsafe {
+        glib::error::Error::wrap(glib_ffi::g_error_new_literal(
+            io_error_quark(),
+            IO_ERROR_INVALID_ARGUMENT,
+            message.to_glib_none().0,
+        ))
+    }
+}
+
+/// Creates the error returned by `Notification::show` for notifications
+/// dropped by the rate limit.
+pub(crate) fn rate_limited() -> glib::error::Error {
+    unsafe {
+        glib::error::Error::wrap(glib_ffi::g_error_new_literal(
+            libnotify_error_quark(),
+            RATE_LIMITED,
+            "Notification dropped by the rate limit".to_glib_none().0,
+        ))
+    }
+}
+
+fn dbus_error_quark() -> glib_ffi::GQuark {
+    unsafe {
+        glib_ffi::g_quark_from_static_string(
+            b"g-dbus-error-quark\0".as_ptr() as *const _,
+        )
+    }
+}
+
+fn io_error_quark() -> glib_ffi::GQuark {
+    unsafe {
+        glib_ffi::g_quark_from_static_string(
+            b"g-io-error-quark\0".as_ptr() as *const _,
+        )
+    }
+}
+
+fn libnotify_error_quark() -> glib_ffi::GQuark {
+    unsafe {
+        glib_ffi::g_quark_from_static_string(
+            b"rust-libnotify-error-quark\0".as_ptr() as *const _,
+        )
+    }
+}
@@ -1,12 +1,70 @@
 use ffi;
+use glib;
 use glib::translate::*;
 use glib_ffi;
+#[cfg(feature = "gtk")]
+use gtk;
+#[cfg(feature = "gtk")]
+use std::path::PathBuf;
+use std::collections::VecDeque;
+use std::ffi::CStr;
+use std::os::raw::c_char;
 use std::ptr;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+
+type GlobalHook = Arc<dyn Fn(&str, Option<&str>) + Send + Sync>;
+
+static GLOBAL_HOOK: Mutex<Option<GlobalHook>> = Mutex::new(None);
+
+static DEFAULT_HINTS: Mutex<Vec<(String, glib::Variant)>> =
+    Mutex::new(Vec::new());
+
+static DEFAULT_ICON: Mutex<Option<String>> = Mutex::new(None);
+
+static RATE_LIMIT: Mutex<Option<RateLimit>> = Mutex::new(None);
+
+// Held while libnotify is initialized or uninitialized, and by `with_raw`.
+static INIT_LOCK: Mutex<()> = Mutex::new(());
+
+struct RateLimit {
+    max: u32,
+    per: Duration,
+    shown: VecDeque<Instant>,
+    dropped: u64,
+}
+
+impl RateLimit {
+    fn allows(&mut self, now: Instant) -> bool {
+        while self
+            .shown
+            .front()
+            .is_some_and(|&shown| now.duration_since(shown) >= self.per)
+        {
+            self.shown.pop_front();
+        }
+        if self.shown.len() >= self.max as usize {
+            self.dropped += 1;
+            return false;
+        }
+        true
+    }
+
+    fn record(&mut self, now: Instant) {
+        self.shown.push_back(now);
+    }
+}
 
 
 
 /// Gets whether or not libnotify is initialized.
 ///
+/// Libraries that show notifications on behalf of an application can use
+/// this to reuse the application's initialization instead of calling
+/// `init()` and `uninit()` themselves, which would change the application
+/// name or tear libnotify down under the application.
+///
 /// # Returns
 ///
 /// `true` if libnotify is initialized, or `false` otherwise.
@@ -16,11 +74,21 @@ pub fn is_initted() -> bool {
 
 /// Initialized libnotify. This must be called before any other functions.
 ///
+/// The initialization is global to the process. Calling `init` again while
+/// libnotify is initialized succeeds without changing the application name,
+/// use `set_app_name()` for that. After `uninit()`, `init` may be called
+/// again.
+///
 /// # Returns
 ///
 /// `Ok(())` if successful, `Err(str)` on error.
 // TODO: switch back to BoolError when it hits stable glib
 pub fn init(app_name: &str) -> Result<(), String> {
+    let _lock = lock_init();
+    init_locked(app_name)
+}
+
+fn init_locked(app_name: &str) -> Result<(), String> {
     unsafe {
         let b = ffi::notify_init(app_name.to_glib_none().0);
 
@@ -48,7 +116,8 @@ pub fn get_app_name() -> Option<String> {
 ///
 /// # Returns
 ///
-/// A Vector of server capability Strings.
+/// A Vector of server capability Strings. Invalid UTF-8 is replaced with
+/// U+FFFD.
 pub fn get_server_caps() -> Vec<String> {
     assert_initialized_libnotify!();
     unsafe {
@@ -56,10 +125,47 @@ pub fn get_server_caps() -> Vec<String> {
     }
 }
 
+/// Synchronously queries the server for its capabilities and checks whether
+/// `capability` is among them.
+/// ## `capability`
+/// The capability, e.g. `"actions"` or `"body-markup"`.
+///
+/// # Returns
+///
+/// `true` if the server advertises `capability`, `false` otherwise.
+pub fn server_supports(capability: &str) -> bool {
+    get_server_caps().iter().any(|cap| cap == capability)
+}
+
+/// Looks up a themed icon in the default GTK icon theme and resolves it to a
+/// file, for servers that do not look up theme icons themselves. GTK must
+/// have been initialized on the calling thread.
+///
+/// This requires the `gtk` feature.
+/// ## `name`
+/// The icon name, e.g. `"dialog-information"`.
+/// ## `size`
+/// The desired icon size in pixels.
+///
+/// # Returns
+///
+/// The absolute path of the icon file, or `None` if the icon was not found.
+#[cfg(feature = "gtk")]
+pub fn resolve_icon(name: &str, size: i32) -> Option<PathBuf> {
+    use gtk::{IconInfoExt, IconThemeExt};
+
+    gtk::IconTheme::get_default()?
+        .lookup_icon(name, size, gtk::IconLookupFlags::empty())?
+        .get_filename()
+}
+
 /// Synchronously queries the server for its information, specifically,
 /// the name, vendor, server version, and the version of the notifications
 /// specification that it is compliant with.
 ///
+/// Invalid UTF-8 in the returned strings is replaced with U+FFFD, see
+/// `get_server_info_strict()` to detect it instead.
+///
 /// # Returns
 ///
 /// `Some(ret_name, ret_vendor, ret_version, ret_spec_version)` on
@@ -90,6 +196,72 @@ pub fn get_server_info() -> Option<(String, String, String, String)> {
     }
 }
 
+/// Synchronously queries the server for the version of the notifications
+/// specification it is compliant with. Features introduced by a later
+/// version of the specification may be unavailable, in addition to those
+/// missing from `get_server_caps()`, which only covers optional features.
+///
+/// # Returns
+///
+/// `Some((major, minor))` on success, otherwise `None` if the query failed
+/// or the version is not of the form `major.minor`.
+pub fn spec_version() -> Option<(u32, u32)> {
+    let (_, _, _, spec_version) = get_server_info()?;
+    parse_spec_version(&spec_version)
+}
+
+/// Parses a `major.minor` specification version, as `spec_version()`.
+fn parse_spec_version(spec_version: &str) -> Option<(u32, u32)> {
+    let mut parts = spec_version.trim().splitn(2, '.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
+/// Like `get_server_info()`, but fails instead of replacing invalid UTF-8,
+/// for callers that need the exact strings reported by the server.
+///
+/// # Returns
+///
+/// `Ok(ret_name, ret_vendor, ret_version, ret_spec_version)` on success,
+/// otherwise `Err(str)` if the query failed or a string is not valid UTF-8.
+pub fn get_server_info_strict()
+    -> Result<(String, String, String, String), String> {
+    assert_initialized_libnotify!();
+    unsafe {
+        let mut ret_name = ptr::null_mut();
+        let mut ret_vendor = ptr::null_mut();
+        let mut ret_version = ptr::null_mut();
+        let mut ret_spec_version = ptr::null_mut();
+        let ret: bool = from_glib(ffi::notify_get_server_info(
+            &mut ret_name,
+            &mut ret_vendor,
+            &mut ret_version,
+            &mut ret_spec_version,
+        ));
+        if !ret {
+            return Err(String::from("Failed to query server information"));
+        }
+
+        let name = take_utf8(ret_name);
+        let vendor = take_utf8(ret_vendor);
+        let version = take_utf8(ret_version);
+        let spec_version = take_utf8(ret_spec_version);
+        Ok((name?, vendor?, version?, spec_version?))
+    }
+}
+
+/// Takes ownership of a string returned by libnotify and converts it
+/// without replacing invalid UTF-8.
+unsafe fn take_utf8(ptr: *mut c_char) -> Result<String, String> {
+    let string = CStr::from_ptr(ptr)
+        .to_str()
+        .map(String::from)
+        .map_err(|_| String::from("Invalid UTF-8 in server information"));
+    glib_ffi::g_free(ptr as glib_ffi::gpointer);
+    string
+}
+
 /// Sets the application name.
 /// ## `app_name`
 /// The name of the application.
@@ -103,10 +275,301 @@ pub fn set_app_name(app_name: &str) {
 /// Uninitialized libnotify.
 ///
 /// This should be called when the program no longer needs libnotify for
-/// the rest of its lifecycle, typically just before exitting.
+/// the rest of its lifecycle, typically just before exitting. Any further
+/// call that requires libnotify to be initialized panics until `init()` is
+/// called again.
 pub fn uninit() {
     assert_initialized_libnotify!();
+    let _lock = lock_init();
     unsafe {
         ffi::notify_uninit();
     }
 }
+
+pub(crate) fn uninit_if_initted() {
+    let _lock = lock_init();
+    if is_initted() {
+        unsafe {
+            ffi::notify_uninit();
+        }
+    }
+}
+
+/// Uninitializes libnotify if it is initialized and initializes it again
+/// with `app_name`. This also resets the connection to the notification
+/// server, e.g. after it was restarted. To only change the application
+/// name, `set_app_name()` is enough.
+///
+/// Existing notifications stay usable, but libnotify forgets about them
+/// while it reconnects, so signals such as `closed` or actions of
+/// notifications shown before may no longer be delivered.
+/// ## `app_name`
+/// The new application name.
+///
+/// # Returns
+///
+/// `Ok(())` if successful, `Err(str)` on error, as for `init()`.
+pub fn reinit(app_name: &str) -> Result<(), String> {
+    let _lock = lock_init();
+    if is_initted() {
+        unsafe {
+            ffi::notify_uninit();
+        }
+    }
+    init_locked(app_name)
+}
+
+/// Runs `f` while holding the lock that `init()`, `uninit()` and
+/// `reinit()` take, so that raw `libnotify-sys` calls made by `f` do not
+/// race with another thread initializing or uninitializing libnotify
+/// through this crate. Other calls of this crate do not take the lock.
+///
+/// `f` must not call `init()`, `uninit()`, `reinit()`, `with_raw()` or
+/// `init_guarded()`, or drop an `InitGuard`, which would deadlock, and must
+/// not call `notify_uninit` directly, which would leave libnotify
+/// uninitialized behind this crate's back.
+/// ## `f`
+/// The closure to run.
+///
+/// # Returns
+///
+/// The return value of `f`.
+pub fn with_raw<R, F: FnOnce() -> R>(f: F) -> R {
+    let _lock = lock_init();
+    f()
+}
+
+fn lock_init() -> MutexGuard<'static, ()> {
+    lock(&INIT_LOCK)
+}
+
+// None of the locked settings can be left half-updated, so a panic while a
+// lock is held, e.g. in `with_raw`, is no reason to fail every later call.
+pub(crate) fn lock<T>(mutex: &'static Mutex<T>) -> MutexGuard<'static, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Dispatches everything that is pending on the thread-default main
+/// context, without blocking. Programs that do not run a main loop, such as
+/// short-lived command line tools, should call this before exiting, so that
+/// pending D-Bus traffic and signals of notifications are processed and the
+/// notification is not lost with the process.
+///
+/// `Notification::show` itself waits for the server to reply, so this is
+/// only needed for work that libnotify or GLib queue on the main context.
+pub fn flush() {
+    if let Some(context) = glib::MainContext::ref_thread_default() {
+        while context.pending() {
+            context.iteration(false);
+        }
+    }
+}
+
+/// Sets a hook that is invoked whenever any notification in the process is
+/// successfully shown through `Notification::show`, receiving its summary and
+/// body. This is useful to log every notification or mirror it elsewhere,
+/// e.g. into an in-app activity feed. Setting a new hook replaces the
+/// previous one.
+///
+/// The hook may show notifications itself, which invoke it again, and
+/// set or clear the hook, which takes effect from the next notification.
+/// ## `hook`
+/// The function to invoke.
+pub fn set_global_hook<F>(hook: F)
+where
+    F: Fn(&str, Option<&str>) + Send + Sync + 'static,
+{
+    *lock(&GLOBAL_HOOK) = Some(Arc::new(hook));
+}
+
+/// Removes the hook set with `set_global_hook`, if any.
+pub fn clear_global_hook() {
+    *lock(&GLOBAL_HOOK) = None;
+}
+
+pub(crate) fn run_global_hook(summary: &str, body: Option<&str>) {
+    // Called without the lock held, so that the hook can show notifications.
+    let hook = lock(&GLOBAL_HOOK).clone();
+    if let Some(hook) = hook {
+        hook(summary, body);
+    }
+}
+
+/// Sets hints that are applied to every notification created afterwards,
+/// e.g. a `desktop-entry` hint identifying the application. This replaces any
+/// defaults set before, and an empty `hints` removes them.
+///
+/// Hints set on a notification itself take precedence over the defaults.
+/// ## `hints`
+/// The hint keys and values.
+///
+/// # Returns
+///
+/// `Ok(())` on success, or `Err(str)` if a key contains a nul byte
+pub fn set_default_hints(
+    hints: Vec<(String, glib::Variant)>,
+) -> Result<(), String> {
+    if hints.iter().any(|(key, _)| key.contains('\0')) {
+        return Err(String::from("Nul byte in hint key"));
+    }
+
+    *lock(&DEFAULT_HINTS) = hints;
+    Ok(())
+}
+
+pub(crate) fn default_hints() -> Vec<(String, glib::Variant)> {
+    lock(&DEFAULT_HINTS).clone()
+}
+
+/// Sets the icon used by every notification created afterwards without an
+/// icon of its own, so that applications do not need to pass it on every
+/// call. An icon passed to `Notification::new` always takes precedence, and
+/// `None` removes the default.
+/// ## `icon`
+/// The icon theme icon name or filename.
+///
+/// # Returns
+///
+/// `Ok(())` on success, or `Err(str)` if `icon` contains a nul byte
+pub fn set_default_icon<'a, P: Into<Option<&'a str>>>(
+    icon: P,
+) -> Result<(), String> {
+    let icon = icon.into();
+    if icon.is_some_and(|icon| icon.contains('\0')) {
+        return Err(String::from("Nul byte in icon"));
+    }
+
+    *lock(&DEFAULT_ICON) = icon.map(str::to_owned);
+    Ok(())
+}
+
+pub(crate) fn default_icon() -> Option<String> {
+    lock(&DEFAULT_ICON).clone()
+}
+
+/// Limits how many new notifications the process shows, to protect users
+/// from floods caused by e.g. a misbehaving event loop. Once `max`
+/// notifications were shown within the last `per`, further calls to
+/// `Notification::show` for notifications that are not shown yet are
+/// dropped: they fail without contacting the server, with an error for
+/// which `NotificationErrorExt::is_rate_limited` is `true`, and are counted
+/// by `rate_limited_count()`. Only notifications the server accepted count
+/// against the limit. Updates of already shown notifications are never
+/// dropped. Setting a new limit resets the count.
+/// ## `max`
+/// The maximum number of notifications per `per`.
+/// ## `per`
+/// The time window.
+pub fn set_rate_limit(max: u32, per: Duration) {
+    *lock(&RATE_LIMIT) = Some(RateLimit {
+        max,
+        per,
+        shown: VecDeque::new(),
+        dropped: 0,
+    });
+}
+
+/// Removes the limit set with `set_rate_limit`, if any.
+pub fn clear_rate_limit() {
+    *lock(&RATE_LIMIT) = None;
+}
+
+/// Gets the number of notifications dropped because of the limit set with
+/// `set_rate_limit`.
+///
+/// # Returns
+///
+/// The number of dropped notifications, or `0` if no limit is set.
+pub fn rate_limited_count() -> u64 {
+    lock(&RATE_LIMIT)
+        .as_ref()
+        .map_or(0, |limit| limit.dropped)
+}
+
+/// Checks whether a new notification may be shown, counting a drop if the
+/// rate limit is exceeded. The notification is only counted against the
+/// limit once it was shown, see `rate_limit_record`.
+pub(crate) fn rate_limit_allows() -> bool {
+    lock(&RATE_LIMIT)
+        .as_mut()
+        .is_none_or(|limit| limit.allows(Instant::now()))
+}
+
+/// Records a new notification as shown, if a rate limit is set.
+pub(crate) fn rate_limit_record() {
+    if let Some(ref mut limit) = *lock(&RATE_LIMIT) {
+        limit.record(Instant::now());
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_spec_version_accepts_major_minor() {
+        assert_eq!(parse_spec_version("1.2"), Some((1, 2)));
+        assert_eq!(parse_spec_version("0.9"), Some((0, 9)));
+        assert_eq!(parse_spec_version(" 1.10\n"), Some((1, 10)));
+    }
+
+    #[test]
+    fn parse_spec_version_rejects_other_forms() {
+        for version in &["", "1", "1.", ".2", "1.2.3", "x.y", "1.y", "-1.2"] {
+            assert_eq!(parse_spec_version(version), None, "{:?}", version);
+        }
+    }
+
+    fn rate_limit(max: u32, per: Duration) -> RateLimit {
+        RateLimit {
+            max,
+            per,
+            shown: VecDeque::new(),
+            dropped: 0,
+        }
+    }
+
+    #[test]
+    fn rate_limit_drops_over_max() {
+        let start = Instant::now();
+        let mut limit = rate_limit(2, Duration::from_secs(10));
+        for _ in 0..2 {
+            assert!(limit.allows(start));
+            limit.record(start);
+        }
+        assert!(!limit.allows(start));
+        assert!(!limit.allows(start + Duration::from_secs(9)));
+        assert_eq!(limit.dropped, 2);
+    }
+
+    #[test]
+    fn rate_limit_counts_recorded_only() {
+        let start = Instant::now();
+        let mut limit = rate_limit(1, Duration::from_secs(10));
+        // Failed shows are checked but never recorded.
+        assert!(limit.allows(start));
+        assert!(limit.allows(start));
+        limit.record(start);
+        assert!(!limit.allows(start));
+        assert_eq!(limit.dropped, 1);
+    }
+
+    #[test]
+    fn rate_limit_expires_old_shows() {
+        let start = Instant::now();
+        let mut limit = rate_limit(1, Duration::from_secs(10));
+        limit.record(start);
+        assert!(!limit.allows(start + Duration::from_secs(5)));
+        assert!(limit.allows(start + Duration::from_secs(10)));
+        assert!(limit.shown.is_empty());
+        assert_eq!(limit.dropped, 1);
+    }
+
+    #[test]
+    fn rate_limit_zero_drops_everything() {
+        let mut limit = rate_limit(0, Duration::from_secs(10));
+        assert!(!limit.allows(Instant::now()));
+        assert_eq!(limit.dropped, 1);
+    }
+}
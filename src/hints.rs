@@ -0,0 +1,177 @@
+use glib::{ToVariant, Variant};
+
+
+/// The well-known hints of the notification specification and common
+/// extensions, with the `GVariant` type string of their values.
+///
+/// | Hint | Type |
+/// |------|------|
+/// | `action-icons` | `b` |
+/// | `category` | `s` |
+/// | `desktop-entry` | `s` |
+/// | `image-data`, `image_data`, `icon_data` | `(iiibiiay)` |
+/// | `image-path`, `image_path` | `s` |
+/// | `resident` | `b` |
+/// | `sound-file`, `sound-name` | `s` |
+/// | `suppress-sound` | `b` |
+/// | `transient` | `b` |
+/// | `urgency` | `y` |
+/// | `value` | `i` |
+/// | `x`, `y` | `i` |
+/// | `x-canonical-private-synchronous`, `x-dunst-stack-tag` | `s` |
+/// | `x-kde-urls` | `as` |
+pub const WELL_KNOWN_HINTS: &[(&str, &str)] = &[
+    ("action-icons", "b"),
+    ("category", "s"),
+    ("desktop-entry", "s"),
+    ("icon_data", "(iiibiiay)"),
+    ("image-data", "(iiibiiay)"),
+    ("image-path", "s"),
+    ("image_data", "(iiibiiay)"),
+    ("image_path", "s"),
+    ("resident", "b"),
+    ("sound-file", "s"),
+    ("sound-name", "s"),
+    ("suppress-sound", "b"),
+    ("transient", "b"),
+    ("urgency", "y"),
+    ("value", "i"),
+    ("x", "i"),
+    ("x-canonical-private-synchronous", "s"),
+    ("x-dunst-stack-tag", "s"),
+    ("x-kde-urls", "as"),
+    ("y", "i"),
+];
+
+/// Gets the expected type of a well-known hint, see `WELL_KNOWN_HINTS`.
+/// ## `key`
+/// The hint key.
+///
+/// # Returns
+///
+/// The `GVariant` type string, or `None` if `key` is not a well-known hint.
+pub fn hint_type(key: &str) -> Option<&'static str> {
+    WELL_KNOWN_HINTS
+        .iter()
+        .find(|&&(name, _)| name == key)
+        .map(|&(_, type_)| type_)
+}
+
+/// Converts `value` to the expected type of the well-known hint `key`.
+/// Booleans and integers are converted into each other if the value fits,
+/// with any non-zero integer becoming `true`. Values of unknown hints and
+/// values already of the right type are returned unchanged.
+pub(crate) fn coerce_hint(
+    key: &str,
+    value: Variant,
+) -> Result<Variant, String> {
+    let expected = match hint_type(key) {
+        Some(expected) if expected != value.type_().to_str() => expected,
+        _ => return Ok(value),
+    };
+
+    let coerced = integer_value(&value).and_then(|n| match expected {
+        "b" => Some((n != 0).to_variant()),
+        "y" if n >= 0 && n <= i64::from(u8::MAX) => {
+            Some((n as u8).to_variant())
+        }
+        "i" if n >= i64::from(i32::MIN) &&
+                   n <= i64::from(i32::MAX) => {
+            Some((n as i32).to_variant())
+        }
+        _ => None,
+    });
+
+    coerced.ok_or_else(|| {
+        format!(
+            "Hint {} expects type {}, not {}",
+            key,
+            expected,
+            value.type_().to_str()
+        )
+    })
+}
+
+fn integer_value(value: &Variant) -> Option<i64> {
+    value
+        .get::<bool>()
+        .map(i64::from)
+        .or_else(|| value.get::<u8>().map(i64::from))
+        .or_else(|| value.get::<i16>().map(i64::from))
+        .or_else(|| value.get::<u16>().map(i64::from))
+        .or_else(|| value.get::<i32>().map(i64::from))
+        .or_else(|| value.get::<u32>().map(i64::from))
+        .or_else(|| value.get::<i64>())
+}
+
+/// Parses a `TYPE:NAME:VALUE` hint, see `Notification::set_hint_str`.
+pub(crate) fn parse_hint(spec: &str) -> Result<(&str, Variant), String> {
+    let mut parts = spec.splitn(3, ':');
+    let (type_, key, value) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(type_), Some(key), Some(value)) if !key.is_empty() => {
+            (type_, key, value)
+        }
+        _ => {
+            return Err(format!(
+                "Invalid hint {}, expected TYPE:NAME:VALUE",
+                spec
+            ))
+        }
+    };
+    let invalid = || {
+        format!("Invalid {} value for hint {}: {}", type_, key, value)
+    };
+
+    let value = match type_ {
+        "boolean" => match value {
+            "true" => true.to_variant(),
+            "false" => false.to_variant(),
+            _ => return Err(invalid()),
+        },
+        "byte" => value.parse::<u8>().map_err(|_| invalid())?.to_variant(),
+        "double" => value.parse::<f64>().map_err(|_| invalid())?.to_variant(),
+        "int" => value.parse::<i32>().map_err(|_| invalid())?.to_variant(),
+        "string" => value.to_variant(),
+        _ => return Err(format!("Unknown hint type {}", type_)),
+    };
+    Ok((key, value))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_hint_types() {
+        let (key, value) = parse_hint("int:value:42").unwrap();
+        assert_eq!((key, value.get::<i32>()), ("value", Some(42)));
+
+        let (key, value) = parse_hint("double:ratio:0.5").unwrap();
+        assert_eq!((key, value.get::<f64>()), ("ratio", Some(0.5)));
+
+        let (key, value) = parse_hint("string:category:a:b").unwrap();
+        assert_eq!((key, value.get_str()), ("category", Some("a:b")));
+
+        let (key, value) = parse_hint("byte:urgency:2").unwrap();
+        assert_eq!((key, value.get::<u8>()), ("urgency", Some(2)));
+
+        let (key, value) = parse_hint("boolean:transient:true").unwrap();
+        assert_eq!((key, value.get::<bool>()), ("transient", Some(true)));
+        let (_, value) = parse_hint("boolean:transient:false").unwrap();
+        assert_eq!(value.get::<bool>(), Some(false));
+    }
+
+    #[test]
+    fn parse_hint_rejects_bad_input() {
+        assert!(parse_hint("int:value").is_err());
+        assert!(parse_hint("int::42").is_err());
+        assert!(parse_hint("int:value:forty-two").is_err());
+        assert!(parse_hint("int:value:4294967296").is_err());
+        assert!(parse_hint("byte:urgency:256").is_err());
+        assert!(parse_hint("byte:urgency:-1").is_err());
+        assert!(parse_hint("double:ratio:half").is_err());
+        assert!(parse_hint("boolean:transient:yes").is_err());
+        assert!(parse_hint("uint:value:42").is_err());
+    }
+}
@@ -0,0 +1,21 @@
+use gdk_pixbuf;
+use std::path::PathBuf;
+
+
+/// The single image of a notification, see
+/// `Notification::set_primary_image`.
+///
+/// A notification can carry an app icon as well as the `image-data` and
+/// `image-path` hints. When several are set, servers pick one according to
+/// the precedence of the specification, `image-data` before `image-path`
+/// before the app icon, so a custom image may be silently replaced by
+/// another one that was set earlier.
+#[derive(Clone, Debug)]
+pub enum ImageSource {
+    /// An icon theme icon name, sent as the app icon.
+    ThemeIcon(String),
+    /// An image file, sent as the `image-path` hint.
+    File(PathBuf),
+    /// Raw image data, sent as the `image-data` hint.
+    Data(gdk_pixbuf::Pixbuf),
+}
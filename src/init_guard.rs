@@ -0,0 +1,101 @@
+use functions;
+use functions::lock;
+use std::sync::Mutex;
+
+
+// The number of live guards. It is held while libnotify is initialized for
+// a new guard or uninitialized for the last one, so the two cannot race.
+static GUARDS: Mutex<GuardCount> = Mutex::new(GuardCount(0));
+
+#[derive(Debug)]
+struct GuardCount(usize);
+
+impl GuardCount {
+    fn acquire(&mut self) {
+        self.0 += 1;
+    }
+
+    /// Whether the released guard was the last one.
+    fn release(&mut self) -> bool {
+        self.0 = self.0.saturating_sub(1);
+        self.0 == 0
+    }
+}
+
+
+/// Proof that libnotify was initialized by `init_guarded()`. Dropping the
+/// last guard uninitializes libnotify again, so that tests and plugins
+/// cannot forget to call `uninit()`.
+#[must_use = "libnotify is uninitialized when the last InitGuard is dropped"]
+#[derive(Debug)]
+pub struct InitGuard(());
+
+impl Drop for InitGuard {
+    fn drop(&mut self) {
+        let mut guards = lock(&GUARDS);
+        if guards.release() {
+            functions::uninit_if_initted();
+        }
+    }
+}
+
+
+/// Initializes libnotify like `init()`, and returns a guard that calls
+/// `uninit()` when it is dropped. Code using `init()` and `uninit()` keeps
+/// working unchanged; to migrate, keep the guard alive, e.g. in `main` or in
+/// a test, instead of calling `uninit()` at the end.
+///
+/// Guards are counted, so nested guards, e.g. of a plugin and its host, may
+/// be dropped in any order: only dropping the last one uninitializes
+/// libnotify. The initialization is still global to the process, not owned
+/// by the guards: dropping the last guard uninitializes libnotify for
+/// everyone, even if it had been initialized before `init_guarded()` was
+/// called, while dropping it after libnotify was uninitialized by other
+/// means does nothing.
+/// ## `app_name`
+/// The name of the application.
+///
+/// # Returns
+///
+/// The guard if successful, `Err(str)` on error, as for `init()`.
+pub fn init_guarded(app_name: &str) -> Result<InitGuard, String> {
+    let mut guards = lock(&GUARDS);
+    functions::init(app_name)?;
+    guards.acquire();
+    Ok(InitGuard(()))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn last_release_uninits() {
+        let mut guards = GuardCount(0);
+        guards.acquire();
+        assert!(guards.release());
+    }
+
+    #[test]
+    fn nested_releases_uninit_once() {
+        let mut guards = GuardCount(0);
+        guards.acquire();
+        guards.acquire();
+        guards.acquire();
+        assert!(!guards.release());
+        assert!(!guards.release());
+        assert!(guards.release());
+
+        guards.acquire();
+        assert!(guards.release());
+    }
+
+    #[test]
+    fn release_without_guards_stays_at_zero() {
+        let mut guards = GuardCount(0);
+        assert!(guards.release());
+        guards.acquire();
+        assert!(guards.release());
+    }
+}
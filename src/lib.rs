@@ -21,20 +21,73 @@
 //! }
 //!
 //! ```
+//!
+//! Notification bodies are plain text by default: on servers advertising
+//! the `body-markup` capability, `Notification::show` escapes characters
+//! like `<` and `&` so that they are displayed as they are. Earlier versions
+//! sent every body unchanged, so bodies containing markup now have to be
+//! marked as such with `Notification::set_markup_enabled`, or be set with
+//! `Notification::set_body_markup`.
+//!
+//! With the `tracing` feature, the following spans and events are emitted
+//! to the [tracing](https://docs.rs/tracing) crate:
+//!
+//! * a `show` span around `Notification::show`, with the `id` and `summary`
+//!   fields, containing a `DEBUG` event once the notification is shown or a
+//!   `WARN` event with an `error` field if showing failed
+//! * a `close` span around `Notification::close`, with the `id` field,
+//!   containing a `WARN` event with an `error` field if closing failed
+//! * a `WARN` event when `Notification::show` drops a notification because
+//!   of the limit set with `set_rate_limit()`
+//! * a `WARN` event with a `server` field when `Notification::set_group`
+//!   knows no grouping hint for the running server
+//! * a `DEBUG` event for every invoked action, with the `id`, `summary` and
+//!   `action` fields, and the `since_show_ms` field holding the milliseconds
+//!   since the notification was last shown, which helps diagnosing delayed
+//!   callbacks. This costs one timestamp per `Notification::show`.
 
 #![warn(missing_docs)]
 
+#[cfg(feature = "gtk")]
+extern crate gdk;
 extern crate gdk_pixbuf;
+extern crate gio_sys as gio_ffi;
 #[macro_use]
 extern crate glib;
 extern crate glib_sys as glib_ffi;
 extern crate gobject_sys as gobject_ffi;
+#[cfg(feature = "gtk")]
+extern crate gtk;
 extern crate libnotify_sys as ffi;
+#[cfg(feature = "tracing")]
+#[macro_use]
+extern crate tracing;
 
 
+#[cfg(feature = "audit-log")]
+pub use audit::*;
+pub use builder::*;
+pub use category::*;
+pub use channel::*;
+pub use closed_reason::*;
+pub use config::*;
 pub use enums::*;
+pub use error::*;
 pub use functions::*;
+pub use hints::*;
+pub use image_source::*;
+pub use init_guard::*;
+pub use markup::*;
+#[cfg(feature = "mock")]
+pub use mock::*;
 pub use notification::*;
+pub use pool::*;
+pub use server::*;
+pub use sound::*;
+pub use spec::*;
+pub use template::*;
+pub use timeout::*;
+pub use watch::*;
 
 
 macro_rules! assert_initialized_libnotify {
@@ -47,6 +100,93 @@ macro_rules! assert_initialized_libnotify {
 }
 
 
+/// Creates and shows a notification in one go.
+///
+/// The summary is followed by optional `key = value` pairs, separated from
+/// it by a semicolon:
+///
+/// * `body = &str`, the body text
+/// * `icon = &str`, the icon theme icon name or filename
+/// * `urgency = Urgency`, the urgency, which may be given as a bare variant
+///   name such as `Critical`
+/// * `timeout = i32`, the timeout in milliseconds
+/// * `category = &str`, the category
+///
+/// The macro evaluates to `Result<Notification, glib::error::Error>`, the
+/// result of `Notification::show`.
+///
+/// ```no_run
+/// #[macro_use]
+/// extern crate libnotify;
+/// extern crate glib;
+///
+/// fn run() -> Result<(), glib::error::Error> {
+///     libnotify::init("myapp").unwrap();
+///     notify!("Summary")?;
+///     notify!("Disk full"; body = "Only 3% left", urgency = Critical)?;
+///     libnotify::uninit();
+///     Ok(())
+/// }
+/// # fn main() { run().unwrap() }
+/// ```
+#[macro_export]
+macro_rules! notify {
+    ($summary:expr) => {
+        $crate::notify!($summary;)
+    };
+    ($summary:expr; $($key:ident = $value:expr),* $(,)?) => {{
+        let mut body: Option<&str> = None;
+        let mut icon: Option<&str> = None;
+        $($crate::notify!(@text body, icon, $key, $value);)*
+        let notification = $crate::Notification::new($summary, body, icon);
+        $($crate::notify!(@apply notification, $key, $value);)*
+        notification.show().map(|()| notification)
+    }};
+    (@text $body:ident, $icon:ident, body, $value:expr) => {
+        $body = Some($value);
+    };
+    (@text $body:ident, $icon:ident, icon, $value:expr) => {
+        $icon = Some($value);
+    };
+    (@text $body:ident, $icon:ident, $key:ident, $value:expr) => {};
+    (@apply $n:ident, urgency, $value:expr) => {{
+        #[allow(unused_imports)]
+        use $crate::Urgency::*;
+        $n.set_urgency($value);
+    }};
+    (@apply $n:ident, timeout, $value:expr) => {
+        $n.set_timeout($value);
+    };
+    (@apply $n:ident, category, $value:expr) => {
+        $n.set_category($value);
+    };
+    (@apply $n:ident, body, $value:expr) => {};
+    (@apply $n:ident, icon, $value:expr) => {};
+}
+
+#[cfg(feature = "audit-log")]
+mod audit;
+mod builder;
+mod category;
+mod channel;
+mod closed_reason;
+mod config;
 mod enums;
+mod error;
 mod functions;
+mod hints;
+mod image_source;
+mod init_guard;
+mod markup;
+#[cfg(feature = "mock")]
+mod mock;
 mod notification;
+mod pool;
+mod presence;
+mod reply;
+mod server;
+mod sound;
+mod spec;
+mod template;
+mod timeout;
+mod watch;
@@ -0,0 +1,97 @@
+/// Escapes `text` so that it is displayed literally in a notification body on
+/// servers advertising the `body-markup` capability.
+/// ## `text`
+/// The plain text to escape.
+///
+/// # Returns
+///
+/// The escaped text.
+pub fn escape_markup(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Converts body markup to plain text, for servers that do not advertise
+/// the `body-markup` capability. Tags are removed and the predefined
+/// entities (`&amp;`, `&lt;`, `&gt;`, `&quot;` and `&apos;`) are replaced
+/// by the characters they stand for.
+/// ## `markup`
+/// The markup to convert.
+///
+/// # Returns
+///
+/// The plain text.
+pub fn strip_markup(markup: &str) -> String {
+    let mut text = String::with_capacity(markup.len());
+    let mut in_tag = false;
+    for c in markup.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => (),
+        }
+    }
+
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_markup_escapes_special_characters() {
+        assert_eq!(
+            escape_markup("<b>Tom & \"Jerry\"</b> 'n"),
+            "&lt;b&gt;Tom &amp; &quot;Jerry&quot;&lt;/b&gt; &apos;n"
+        );
+        assert_eq!(escape_markup("&amp;"), "&amp;amp;");
+    }
+
+    #[test]
+    fn escape_markup_keeps_plain_text() {
+        assert_eq!(escape_markup(""), "");
+        assert_eq!(escape_markup("héllo\nwörld 日本"), "héllo\nwörld 日本");
+    }
+
+    #[test]
+    fn strip_markup_removes_tags() {
+        assert_eq!(strip_markup("<b>bold</b> and <i>it</i>"), "bold and it");
+        assert_eq!(
+            strip_markup("<a href=\"https://a.b/\">link</a>"),
+            "link"
+        );
+        assert_eq!(strip_markup("1 > 0"), "1 > 0");
+    }
+
+    #[test]
+    fn strip_markup_replaces_entities() {
+        assert_eq!(
+            strip_markup("&lt;tag&gt; &quot;q&quot; &apos;a&apos; &amp;"),
+            "<tag> \"q\" 'a' &"
+        );
+        assert_eq!(strip_markup("&amp;lt;"), "&lt;");
+    }
+
+    #[test]
+    fn strip_markup_inverts_escape_markup() {
+        let text = "a < b && c > \"d\" 'e'";
+        assert_eq!(strip_markup(&escape_markup(text)), text);
+    }
+}
@@ -0,0 +1,232 @@
+use Notification;
+use Urgency;
+use functions::lock;
+use glib;
+use glib::translate::*;
+use glib_ffi;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+
+static HISTORY: Mutex<Vec<ShownRecord>> = Mutex::new(Vec::new());
+
+type ShowResult = Result<(), (u32, i32, String)>;
+
+static SHOW_RESULT: Mutex<Option<ShowResult>> = Mutex::new(None);
+
+
+/// A notification as it was when `Notification::show` succeeded, recorded
+/// by the `mock` feature.
+#[derive(Clone, Debug)]
+pub struct ShownRecord {
+    /// The summary text.
+    pub summary: String,
+    /// The body text, if any.
+    pub body: Option<String>,
+    /// The icon theme icon name or filename, if any.
+    pub icon: Option<String>,
+    /// The urgency, if one was set.
+    pub urgency: Option<Urgency>,
+    /// The hints set through this crate, in the order they were first set.
+    pub hints: Vec<(String, glib::Variant)>,
+    /// When the notification was shown.
+    pub shown_at: SystemTime,
+}
+
+
+/// Gets every notification shown in this process since startup or the last
+/// `clear_history()`, oldest first. This lets integration tests assert on
+/// the notifications an application produced.
+///
+/// Only available with the `mock` feature.
+///
+/// # Returns
+///
+/// The recorded notifications.
+pub fn shown_history() -> Vec<ShownRecord> {
+    lock(&HISTORY).clone()
+}
+
+/// Forgets all notifications recorded so far, see `shown_history()`.
+///
+/// Only available with the `mock` feature.
+pub fn clear_history() {
+    lock(&HISTORY).clear();
+}
+
+pub(crate) fn record_shown(
+    notification: &Notification,
+    summary: String,
+    body: Option<String>,
+) {
+    lock(&HISTORY).push(ShownRecord {
+        summary,
+        body,
+        icon: notification.get_string_property("icon-name"),
+        urgency: notification.cached_urgency(),
+        hints: notification.cached_hints(),
+        shown_at: SystemTime::now(),
+    });
+}
+
+/// Makes every following `Notification::show` return `result` instead of
+/// contacting the notification server, until `clear_mock_show_result()` is
+/// called. This lets tests exercise error handling, such as retries and
+/// fallbacks, without a misbehaving server.
+///
+/// An injected success is treated like a real one: the notification is
+/// recorded in `shown_history()` and the global hook runs, but it gets no
+/// server id. An injected error `(domain, code, message)` is returned as a
+/// `glib::error::Error` with that `GQuark` domain, code and message, so
+/// `NotificationErrorExt::code` returns `code` and e.g.
+/// `NotificationErrorExt::is_timeout` inspects the pair as usual.
+///
+/// Only available with the `mock` feature.
+/// ## `result`
+/// The result to return.
+///
+/// # Panics
+///
+/// Panics if the error message contains a nul byte.
+pub fn set_mock_show_result(result: ShowResult) {
+    if let Err((_, _, ref message)) = result {
+        assert!(!message.contains('\0'), "Nul byte in error message");
+    }
+    *lock(&SHOW_RESULT) = Some(result);
+}
+
+/// Makes `Notification::show` contact the notification server again, see
+/// `set_mock_show_result()`.
+///
+/// Only available with the `mock` feature.
+pub fn clear_mock_show_result() {
+    *lock(&SHOW_RESULT) = None;
+}
+
+/// Applies the result set with `set_mock_show_result`, storing an injected
+/// error in `err`.
+///
+/// # Returns
+///
+/// `true` if a result was injected and the server must not be contacted.
+pub(crate) fn mock_show(err: &mut *mut glib_ffi::GError) -> bool {
+    match *lock(&SHOW_RESULT) {
+        Some(Ok(())) => true,
+        Some(Err((domain, code, ref message))) => {
+            *err = unsafe {
+                glib_ffi::g_error_new_literal(
+                    domain,
+                    code,
+                    message.to_glib_none().0,
+                )
+            };
+            true
+        }
+        None => false,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glib::ToVariant;
+    use init;
+
+    // libnotify is not thread-safe, and tests run in parallel threads.
+    static SERIAL: Mutex<()> = Mutex::new(());
+
+    // Shows a notification configured by `configure` through the mock. The
+    // history is shared by all tests, so each test uses a summary of its own
+    // and only looks at the record with that summary.
+    fn show_mocked<F: FnOnce(&Notification)>(
+        summary: &str,
+        configure: F,
+    ) -> ShownRecord {
+        let _serial = lock(&SERIAL);
+        init("libnotify-mock-tests").unwrap();
+        set_mock_show_result(Ok(()));
+        let notification = Notification::new(summary, None, None);
+        configure(&notification);
+        notification.show().unwrap();
+        shown_history()
+            .into_iter()
+            .rev()
+            .find(|record| record.summary == summary)
+            .expect("shown notification is recorded")
+    }
+
+    fn hint<'a>(
+        record: &'a ShownRecord,
+        key: &str,
+    ) -> Option<&'a glib::Variant> {
+        record
+            .hints
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value)
+    }
+
+    #[test]
+    fn clear_icon_removes_icon_and_images() {
+        let record = show_mocked("clear_icon", |notification| {
+            notification.update("clear_icon", None, "dialog-error").unwrap();
+            let path = "/tmp/icon.png".to_variant();
+            notification.set_hint("image-path", Some(path));
+            notification.clear_icon();
+        });
+        assert_eq!(record.icon.as_deref().unwrap_or(""), "");
+        assert_eq!(hint(&record, "image-path"), None);
+    }
+
+    #[test]
+    fn set_synchronous_records_hint() {
+        let record = show_mocked("set_synchronous", |notification| {
+            notification.set_synchronous("volume").unwrap();
+            assert!(notification.set_synchronous("vol\0ume").is_err());
+        });
+        assert_eq!(
+            hint(&record, "x-canonical-private-synchronous"),
+            Some(&"volume".to_variant())
+        );
+    }
+
+    #[test]
+    fn set_kde_urls_records_string_array() {
+        let urls = ["file:///tmp/a.pdf", "https://example.org/"];
+        let record = show_mocked("set_kde_urls", |notification| {
+            notification.set_kde_urls(&urls).unwrap();
+            assert!(notification.set_kde_urls(&["a", "b\0"]).is_err());
+        });
+        let value = hint(&record, "x-kde-urls").expect("x-kde-urls hint");
+        assert_eq!(value.type_().to_str(), "as");
+        assert_eq!(
+            value.to_string(),
+            "['file:///tmp/a.pdf', 'https://example.org/']"
+        );
+    }
+
+    #[test]
+    fn set_dunst_stack_tag_records_hint() {
+        let record = show_mocked("set_dunst_stack_tag", |notification| {
+            notification.set_dunst_stack_tag("backup").unwrap();
+            assert!(notification.set_dunst_stack_tag("back\0up").is_err());
+        });
+        assert_eq!(
+            hint(&record, "x-dunst-stack-tag"),
+            Some(&"backup".to_variant())
+        );
+    }
+
+    #[test]
+    fn set_accessible_text_records_hint() {
+        let record = show_mocked("set_accessible_text", |notification| {
+            notification.set_accessible_text("Battery at 5 percent").unwrap();
+            assert!(notification.set_accessible_text("\0").is_err());
+        });
+        assert_eq!(
+            hint(&record, "x-accessible-text"),
+            Some(&"Battery at 5 percent".to_variant())
+        );
+    }
+}
@@ -1,13 +1,46 @@
+use ActionError;
+use Category;
+use ClosedReason;
+use ImageSource;
+use KnownServer;
+use NotificationConfig;
+use NotificationErrorExt;
+use SoundName;
+use Template;
+use Timeout;
+use UnsupportedFeature;
 use Urgency;
+use coerce_hint;
+use detect_server;
+use error::{invalid_argument, rate_limited};
+use escape_markup;
 use ffi;
 use gdk_pixbuf;
+use get_server_caps;
 use glib::translate::*;
+use glib::{ObjectExt, ToValue, ToVariant};
 use glib;
 use glib_ffi;
 use gobject_ffi;
+use is_initted;
+use parse_hint;
+use presence::screensaver_active;
+use reply::{ReplySubscription, subscribe_replies};
+use server_supports;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::mem;
+use std::ops::Deref;
+use std::os::raw::c_char;
 use std::ptr;
+use std::rc::Rc;
+use std::thread;
+use std::time::Duration;
+#[cfg(feature = "tracing")]
+use std::time::Instant;
 use std;
+use strip_markup;
 
 
 glib_wrapper! {
@@ -25,6 +58,40 @@ glib_wrapper! {
 }
 
 
+/// Timeout value for `Notification::set_timeout` that leaves the expiration
+/// to the notification server. This is the default for new notifications.
+/// The specification does not provide a way to query how long this is, it
+/// commonly ranges between a few seconds and the notification never expiring.
+pub const EXPIRES_DEFAULT: i32 = ffi::NOTIFY_EXPIRES_DEFAULT;
+
+/// Timeout value for `Notification::set_timeout` that asks the notification
+/// server to never expire the notification.
+pub const EXPIRES_NEVER: i32 = ffi::NOTIFY_EXPIRES_NEVER;
+
+
+/// The number of items `Notification::digest` lists before summing up the
+/// rest in an "and N more" line.
+pub const DIGEST_MAX_ITEMS: usize = 5;
+
+/// The bounds and offset of `Notification::set_timeout_for_reading`.
+const READING_BASE_MS: u64 = 1_000;
+const READING_MIN_MS: u64 = 3_000;
+const READING_MAX_MS: u64 = 30_000;
+
+
+thread_local! {
+    // Every notification created through `Notification::new`, so that they
+    // can be closed in bulk without keeping them alive.
+    static REGISTRY: RefCell<Vec<glib::WeakRef<Notification>>> =
+        const { RefCell::new(Vec::new()) };
+
+    // The notifications shown through `Notification::show_replacing`, by
+    // tag, until they are closed.
+    static TAGGED: RefCell<HashMap<String, Notification>> =
+        RefCell::new(HashMap::new());
+}
+
+
 
 impl Notification {
     /// Creates a new `Notification`. The summary text is required, but
@@ -34,11 +101,12 @@ impl Notification {
     /// ## `body`
     /// The optional body text.
     /// ## `icon`
-    /// The optional icon theme icon name or filename.
+    /// The optional icon theme icon name or filename. If `None`, the icon
+    /// set by `set_default_icon()` is used.
     ///
     /// # Returns
     ///
-    /// The new `Notification`.
+    /// The new `Notification`, with the hints set by `set_default_hints()`.
     pub fn new<'a, 'b, P: Into<Option<&'a str>>, Q: Into<Option<&'b str>>>(
         summary: &str,
         body: P,
@@ -47,15 +115,95 @@ impl Notification {
         assert_initialized_libnotify!();
         let body = body.into();
         let body = body.to_glib_none();
-        let icon = icon.into();
+        let icon = icon.into().map(str::to_owned).or_else(default_icon);
         let icon = icon.to_glib_none();
-        unsafe {
+        let notification: Notification = unsafe {
             from_glib_full(ffi::notify_notification_new(
                 summary.to_glib_none().0,
                 body.0,
                 icon.0,
             ))
+        };
+
+        for (key, value) in default_hints() {
+            notification.set_hint(&key, Some(value));
+        }
+
+        REGISTRY.with(|registry| {
+            let mut registry = registry.borrow_mut();
+            registry.retain(|weak| weak.upgrade().is_some());
+            registry.push(notification.downgrade());
+        });
+
+        notification
+    }
+
+    /// Like `Notification::new`, but refuses to create a notification whose
+    /// summary is empty or consists only of whitespace. The notification
+    /// specification requires a non-empty summary and most servers render
+    /// such notifications as blank pop-ups.
+    /// ## `summary`
+    /// The required summary text.
+    /// ## `body`
+    /// The optional body text.
+    /// ## `icon`
+    /// The optional icon theme icon name or filename.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(notification)` on success, or `Err(str)` if the summary is empty
+    /// or any of the strings contains a nul byte
+    pub fn new_strict<
+        'a,
+        'b,
+        P: Into<Option<&'a str>>,
+        Q: Into<Option<&'b str>>,
+    >(
+        summary: &str,
+        body: P,
+        icon: Q,
+    ) -> Result<Notification, String> {
+        let body = body.into();
+        let icon = icon.into();
+        if summary.trim().is_empty() {
+            return Err(String::from("Summary must not be empty"));
         }
+        check_nul("summary", summary)?;
+        check_nul("body", body.unwrap_or(""))?;
+        check_nul("icon", icon.unwrap_or(""))?;
+
+        Ok(Notification::new(summary, body, icon))
+    }
+
+    /// Consumes the `Notification` and returns the underlying
+    /// `NotifyNotification` without releasing its reference, transferring
+    /// ownership of that reference to the caller. This is meant for passing
+    /// the notification to C code that takes ownership of it.
+    ///
+    /// The reference can be turned back into a `Notification` with
+    /// `glib::translate::from_glib_full`, or must otherwise be released with
+    /// `g_object_unref`.
+    ///
+    /// # Returns
+    ///
+    /// The owned `NotifyNotification` pointer.
+    pub fn into_raw(self) -> *mut ffi::NotifyNotification {
+        let ptr = self.to_glib_none().0;
+        mem::forget(self);
+        ptr
+    }
+
+    /// Shows the notification and returns a guard that closes it again when
+    /// dropped, e.g. for a "Connecting..." notification that should
+    /// disappear at the end of a scope. The guard dereferences to the
+    /// notification, so it can still be updated while shown.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(guard)` on success, or `Err(err)` if showing the notification failed
+    pub fn sticky(self) -> Result<NotificationGuard, glib::error::Error> {
+        self.show()?;
+        Ok(NotificationGuard(self))
     }
 
     /// Synchronously tells the notification server to hide the notification on the screen.
@@ -65,19 +213,305 @@ impl Notification {
     /// `Ok(())` on success, or `Err(err)` on error
     pub fn close(&self) -> Result<(), glib::error::Error> {
         assert_initialized_libnotify!();
+        #[cfg(feature = "tracing")]
+        let _span = debug_span!("close", id = self.get_id()).entered();
         unsafe {
             let mut err: *mut glib_ffi::GError = std::ptr::null_mut();
             ffi::notify_notification_close(self.to_glib_none().0, &mut err);
 
             if !err.is_null() {
-                return Err(glib::error::Error::wrap(err));
+                let err = glib::error::Error::wrap(err);
+                #[cfg(feature = "tracing")]
+                warn!(error = %err, "failed to close notification");
+                return Err(err);
             } else {
                 return Ok(());
             }
         }
     }
 
+    /// Gets every notification created on this thread that has neither
+    /// been dropped nor closed, including those that were never shown.
+    /// This is useful for management views and bulk operations.
+    ///
+    /// The registry only holds weak references, but the returned handles
+    /// are strong ones, so the notifications stay alive at least as long as
+    /// the returned `Vec`.
+    ///
+    /// # Returns
+    ///
+    /// The live notifications, in order of creation.
+    pub fn live_notifications() -> Vec<Notification> {
+        assert_initialized_libnotify!();
+        REGISTRY.with(|registry| {
+            let mut registry = registry.borrow_mut();
+            registry.retain(|weak| weak.upgrade().is_some());
+            registry
+                .iter()
+                .filter_map(|weak| weak.upgrade())
+                .filter(|n| n.get_closed_reason().is_none())
+                .collect()
+        })
+    }
+
+    /// Synchronously closes every notification created on this thread that
+    /// is still shown. Notifications that were never shown or have already
+    /// been closed are skipped, and notifications that have been dropped are
+    /// not kept alive for this purpose.
+    ///
+    /// This is useful on shutdown, to clear lingering notifications.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or the first `Err(err)` encountered
+    pub fn close_all() -> Result<(), glib::error::Error> {
+        assert_initialized_libnotify!();
+        let live = Notification::live_notifications();
+
+        let mut result = Ok(());
+        for notification in live.iter().filter(|n| n.is_shown()) {
+            if let Err(err) = notification.close() {
+                if result.is_ok() {
+                    result = Err(err);
+                }
+            }
+        }
+        result
+    }
+
+    /// Synchronously closes the notification with the server-assigned `id`,
+    /// as returned by `Notification::get_id`, without its `Notification`.
+    /// This is useful when only the id was passed along, e.g. to a plugin.
+    ///
+    /// libnotify has no call for this, so a temporary notification carrying
+    /// `id` is created and closed. The server closes whichever notification
+    /// it knows under `id`, even one sent by another application, and most
+    /// servers silently ignore unknown ids. An original `Notification` for
+    /// the same id in this process does not learn that it was closed until
+    /// the server emits its `closed` signal.
+    /// ## `id`
+    /// The id of the notification.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or `Err(err)` on error
+    pub fn close_id(id: u32) -> Result<(), glib::error::Error> {
+        assert_initialized_libnotify!();
+        let proxy = Notification::new("", None, None);
+        proxy.set_replaces_id(id);
+        proxy.close()
+    }
+
+    /// Shows a notification for `tag`, replacing the one previously shown
+    /// for the same tag on this thread if it is still open. Otherwise a new
+    /// notification is created, grouped with `Notification::set_group`, and
+    /// kept until it is closed. This gives "update the existing toast"
+    /// behavior on every server, since the replaced notification keeps its
+    /// server id.
+    ///
+    /// A notification is evicted from the tag registry when its `closed`
+    /// signal is emitted, which requires a running main loop. Without one,
+    /// closed notifications are kept and shown again for their tag, which
+    /// the server treats like a new notification.
+    /// ## `tag`
+    /// The tag identifying the notification.
+    /// ## `summary`
+    /// The required summary text.
+    /// ## `body`
+    /// The optional body text.
+    /// ## `icon`
+    /// The optional icon theme icon name or filename.
+    ///
+    /// # Returns
+    ///
+    /// The shown notification on success, or `Err(err)` on error, with the
+    /// code `G_IO_ERROR_INVALID_ARGUMENT` if a string contains a nul byte
+    pub fn show_replacing<
+        'a,
+        'b,
+        P: Into<Option<&'a str>>,
+        Q: Into<Option<&'b str>>,
+    >(
+        tag: &str,
+        summary: &str,
+        body: P,
+        icon: Q,
+    ) -> Result<Notification, glib::error::Error> {
+        assert_initialized_libnotify!();
+        let body = body.into();
+        let icon = icon.into();
+        check_nul("summary", summary)
+            .and_then(|()| check_nul("body", body.unwrap_or("")))
+            .and_then(|()| check_nul("icon", icon.unwrap_or("")))
+            .map_err(|err| invalid_argument(&err))?;
+
+        let existing = TAGGED.with(|tagged| tagged.borrow().get(tag).cloned());
+        let notification = match existing {
+            Some(notification) => {
+                notification
+                    .update(summary, body, icon)
+                    .map_err(|err| invalid_argument(&err))?;
+                notification
+            }
+            None => {
+                let notification = Notification::new(summary, body, icon);
+                // A tag with a nul byte cannot be sent, the grouping is
+                // best-effort anyway.
+                let _ = notification.set_group(tag);
+                let key = tag.to_owned();
+                notification.connect_closed(move |closed, _| {
+                    TAGGED.with(|tagged| {
+                        let mut tagged = tagged.borrow_mut();
+                        if tagged.get(&key) == Some(closed) {
+                            tagged.remove(&key);
+                        }
+                    })
+                });
+                TAGGED.with(|tagged| {
+                    tagged
+                        .borrow_mut()
+                        .insert(tag.to_owned(), notification.clone())
+                });
+                notification
+            }
+        };
+
+        notification.show()?;
+        Ok(notification)
+    }
+
+    /// Creates a new, independent notification with the same summary, body,
+    /// icon, urgency, timeout, hints and markup setting as this one. Unlike
+    /// `clone`, which returns another handle to the same notification, the
+    /// copy gets its own id when it is shown, so a configured notification
+    /// can serve as a template.
+    ///
+    /// Only settings made through this crate are copied. Actions, the
+    /// application name and images set with
+    /// `Notification::set_image_from_pixbuf` are not.
+    ///
+    /// # Returns
+    ///
+    /// The new notification, not shown yet.
+    pub fn clone_config(&self) -> Notification {
+        let summary = self.get_string_property("summary").unwrap_or_default();
+        let body = self.get_string_property("body");
+        let icon = self.get_string_property("icon-name");
+        let copy =
+            Notification::new(&summary, body.as_deref(), icon.as_deref());
+
+        copy.set_timeout(self.state().borrow().timeout);
+        copy.set_markup_enabled(self.markup_enabled());
+        for (key, value) in self.cached_hints() {
+            copy.set_hint(&key, Some(value));
+        }
+        copy
+    }
+
+    /// Shows a notification for an error with the conventional look: the
+    /// `dialog-error` icon and sound and `Urgency::Critical`. No category
+    /// is set, since the specification only has categories for errors of
+    /// devices, instant messages, networks and transfers, see
+    /// `Notification::apply_category_defaults` for those. The returned
+    /// notification can be changed and shown again.
+    /// ## `summary`
+    /// The required summary text.
+    /// ## `detail`
+    /// The optional body text.
+    ///
+    /// # Returns
+    ///
+    /// The shown notification on success, or `Err(err)` on error
+    pub fn error(
+        summary: &str,
+        detail: Option<&str>,
+    ) -> Result<Notification, glib::error::Error> {
+        assert_initialized_libnotify!();
+        let notification = Notification::new(summary, detail, "dialog-error");
+        notification.set_urgency(Urgency::Critical);
+        notification
+            .set_sound(SoundName::DialogError)
+            .expect("sound name without nul bytes");
+        notification.show()?;
+        Ok(notification)
+    }
+
+    /// Shows a notification whose body is rendered from `template` with the
+    /// values in `vars`, as with `Template::render`. On servers advertising
+    /// the `body-markup` capability the template may contain markup: the
+    /// values are escaped and markup is enabled for the notification, see
+    /// `Notification::set_markup_enabled`. Placeholders without a value are
+    /// shown as they are, unless the template was made strict with
+    /// `Template::strict`.
+    /// ## `summary`
+    /// The required summary text.
+    /// ## `template`
+    /// The body template.
+    /// ## `vars`
+    /// The values by placeholder name.
+    /// ## `icon`
+    /// The optional icon theme icon name or filename.
+    ///
+    /// # Returns
+    ///
+    /// The shown notification on success, or `Err(err)` on error, with the
+    /// code `G_IO_ERROR_INVALID_ARGUMENT` and a message naming the missing
+    /// placeholders if a strict template lacks values
+    pub fn show_template<'a, P: Into<Option<&'a str>>>(
+        summary: &str,
+        template: &Template,
+        vars: &HashMap<&str, &str>,
+        icon: P,
+    ) -> Result<Notification, glib::error::Error> {
+        assert_initialized_libnotify!();
+        let markup = server_supports("body-markup");
+        let body = template
+            .render_for_show(vars, markup)
+            .map_err(|err| invalid_argument(&err))?;
+
+        let notification = Notification::new(summary, body.as_str(), icon);
+        notification.set_markup_enabled(markup);
+        notification.show()?;
+        Ok(notification)
+    }
+
+    /// Shows a single notification summarizing several events, e.g. "5 new
+    /// messages" followed by one line per message. At most
+    /// `DIGEST_MAX_ITEMS` items are listed, the rest are counted in a final
+    /// "and N more" line. On servers advertising the `body-markup`
+    /// capability the items are bulleted and the final line is set in
+    /// italics, with markup enabled for the notification, see
+    /// `Notification::set_markup_enabled`. Other servers get the items as
+    /// plain lines.
+    /// ## `title`
+    /// The required summary text.
+    /// ## `items`
+    /// The items to list.
+    /// ## `icon`
+    /// The optional icon theme icon name or filename.
+    ///
+    /// # Returns
+    ///
+    /// The shown notification on success, or `Err(err)` on error
+    pub fn digest<'a, P: Into<Option<&'a str>>>(
+        title: &str,
+        items: &[&str],
+        icon: P,
+    ) -> Result<Notification, glib::error::Error> {
+        assert_initialized_libnotify!();
+        let markup = server_supports("body-markup");
+        let body = digest_body(items, markup);
+        let notification = Notification::new(title, body.as_str(), icon);
+        notification.set_markup_enabled(markup);
+        notification.show()?;
+        Ok(notification)
+    }
+
     /// Tells the notification server to display the notification on the screen.
+    /// Unless markup is enabled, the body is escaped first, see
+    /// `Notification::set_markup_enabled`. New notifications may be dropped
+    /// by the limit set with `set_rate_limit()`, which is reported as an
+    /// error for which `NotificationErrorExt::is_rate_limited` is `true`.
     ///
     /// # Returns
     ///
@@ -85,22 +519,286 @@ impl Notification {
     // TODO: test if Error leaks memory
     pub fn show(&self) -> Result<(), glib::error::Error> {
         assert_initialized_libnotify!();
+        if let Some(ref mut pending) = self.state().borrow_mut().batch {
+            *pending = true;
+            return Ok(());
+        }
+        let new = !self.is_shown();
+        if new && !rate_limit_allows() {
+            #[cfg(feature = "tracing")]
+            warn!("notification dropped by the rate limit");
+            return Err(rate_limited());
+        }
+        let summary = self.get_string_property("summary").unwrap_or_default();
+        #[cfg(feature = "tracing")]
+        let _span = debug_span!(
+            "show",
+            id = self.get_id(),
+            summary = summary.as_str()
+        ).entered();
+        let body = self.get_string_property("body");
+        let escaped = match body {
+            Some(ref body) if !self.markup_enabled() => {
+                Some(escape_markup(body))
+                    .filter(|escaped| escaped != body)
+                    .filter(|_| server_supports("body-markup"))
+            }
+            _ => None,
+        };
+        if let Some(ref escaped) = escaped {
+            self.set_string_property("body", escaped);
+        }
         unsafe {
             let mut err: *mut glib_ffi::GError = std::ptr::null_mut();
-            ffi::notify_notification_show(self.to_glib_none().0, &mut err);
+            #[cfg(feature = "mock")]
+            let mocked = ::mock::mock_show(&mut err);
+            #[cfg(not(feature = "mock"))]
+            let mocked = false;
+            if !mocked {
+                ffi::notify_notification_show(self.to_glib_none().0, &mut err);
+            }
+            // The escaped body is only sent, the caller keeps seeing theirs.
+            if let (Some(_), Some(ref body)) = (&escaped, &body) {
+                self.set_string_property("body", body);
+            }
 
             if !err.is_null() {
-                return Err(glib::error::Error::wrap(err));
-            } else {
-                return Ok(());
+                let err = glib::error::Error::wrap(err);
+                #[cfg(feature = "tracing")]
+                warn!(error = %err, "failed to show notification");
+                return Err(err);
+            }
+        }
+        #[cfg(feature = "tracing")]
+        {
+            self.state().borrow_mut().shown_at = Some(Instant::now());
+            debug!(id = self.get_id(), "notification shown");
+        }
+
+        if new {
+            rate_limit_record();
+        }
+        run_global_hook(&summary, body.as_deref());
+        #[cfg(feature = "audit-log")]
+        ::audit::log_shown(self, &summary, body.as_deref());
+        #[cfg(feature = "mock")]
+        ::mock::record_shown(self, summary, body);
+        Ok(())
+    }
+
+    /// Sets whether the body is sent as markup. When disabled, which is the
+    /// default, `Notification::show` escapes the body on servers advertising
+    /// the `body-markup` capability, so that characters like `<` and `&` are
+    /// displayed as they are. When enabled, the body is sent unchanged and
+    /// markup-capable servers render it. Servers without the capability
+    /// always display the body as plain text.
+    ///
+    /// `Notification::set_body_markup` and `Notification::set_body_with_link`
+    /// enable markup.
+    ///
+    /// Escaping by default is a change in behavior: earlier versions sent
+    /// every body unchanged. Code that passes markup to e.g.
+    /// `Notification::new` or `Notification::update` has to enable markup
+    /// for it to be rendered.
+    /// ## `enabled`
+    /// Whether the body is markup.
+    pub fn set_markup_enabled(&self, enabled: bool) {
+        self.state().borrow_mut().markup = enabled;
+    }
+
+    /// Whether the body is sent as markup, see
+    /// `Notification::set_markup_enabled`.
+    ///
+    /// # Returns
+    ///
+    /// `true` if markup is enabled, `false` otherwise.
+    pub fn markup_enabled(&self) -> bool {
+        self.state().borrow().markup
+    }
+
+    /// Like `Notification::show`, but reports how the server handled the
+    /// notification, by comparing its id before and after showing it with
+    /// those of the other notifications created on this thread. This helps
+    /// diagnosing servers that collapse notifications. It is a heuristic:
+    /// servers are free to assign ids as they like, and notifications shown
+    /// by other processes are unknown here.
+    ///
+    /// # Returns
+    ///
+    /// How the notification was shown, or `Err(err)` on error
+    pub fn show_tracked(&self) -> Result<ShowOutcome, glib::error::Error> {
+        if self.state().borrow().batch.is_some() {
+            self.show()?;
+            return Ok(ShowOutcome::Deferred);
+        }
+        let previous = self.get_id();
+        match self.show() {
+            Err(ref err) if err.is_rate_limited() => {
+                return Ok(ShowOutcome::Dropped)
+            }
+            result => result?,
+        }
+        let id = self.get_id();
+        if id == 0 {
+            return Ok(ShowOutcome::Dropped);
+        }
+
+        let shared = REGISTRY.with(|registry| {
+            registry
+                .borrow()
+                .iter()
+                .filter_map(|weak| weak.upgrade())
+                .any(|other| other != *self && other.get_id() == id)
+        });
+        Ok(if shared {
+            ShowOutcome::Merged(id)
+        } else if previous == 0 {
+            ShowOutcome::New(id)
+        } else if previous == id {
+            ShowOutcome::Replaced(id)
+        } else {
+            ShowOutcome::Reassigned { previous, id }
+        })
+    }
+
+    /// Starts a batch of changes. Until `Notification::commit_batch` is
+    /// called, `Notification::show` only records that the notification
+    /// should be shown, so that many updates in quick succession, e.g. of a
+    /// progress bar, result in a single call to the server. Only the final
+    /// state is displayed. Calling this during a batch does nothing.
+    pub fn begin_batch(&self) {
+        let mut state = self.state().borrow_mut();
+        if state.batch.is_none() {
+            state.batch = Some(false);
+        }
+    }
+
+    /// Ends the batch started by `Notification::begin_batch`, showing the
+    /// notification once if `Notification::show` was called during the
+    /// batch. Outside of a batch this does nothing.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or `Err(err)` on error
+    pub fn commit_batch(&self) -> Result<(), glib::error::Error> {
+        let pending = self.state().borrow_mut().batch.take();
+        match pending {
+            Some(true) => self.show(),
+            _ => Ok(()),
+        }
+    }
+
+    /// Ends a batch without showing the notification.
+    pub(crate) fn discard_batch(&self) {
+        self.state().borrow_mut().batch = None;
+    }
+
+    /// Like `Notification::show`, but retries when the notification server is
+    /// briefly unavailable, e.g. while the session is being restarted.
+    ///
+    /// Only transient errors are retried, i.e. those for which
+    /// `NotificationErrorExt::is_timeout` or
+    /// `NotificationErrorExt::is_service_unknown` is `true`. Any other error
+    /// is returned immediately.
+    /// ## `attempts`
+    /// The maximum number of attempts, including the first one.
+    /// ## `delay`
+    /// The time to wait between attempts.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or the last `Err(err)` on error
+    pub fn show_with_retry(
+        &self,
+        attempts: u32,
+        delay: Duration,
+    ) -> Result<(), glib::error::Error> {
+        let mut remaining = attempts;
+        loop {
+            remaining = remaining.saturating_sub(1);
+            match self.show() {
+                Err(ref err) if remaining > 0 &&
+                                    (err.is_timeout() ||
+                                         err.is_service_unknown()) => {
+                    thread::sleep(delay)
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Shows the notification and runs a main loop until the notification is
+    /// closed or `max` has elapsed, whichever comes first. This keeps tools
+    /// without a main loop of their own from waiting forever if the server
+    /// never reports the notification as closed.
+    ///
+    /// The default main context must not be iterated by another thread while
+    /// this function runs.
+    /// ## `max`
+    /// The maximum time to wait for the notification to be closed.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(Some(reason))` if the notification was closed, `Ok(None)` if it
+    /// was not closed within `max` (it may still be on the screen), or
+    /// `Err(err)` if showing the notification failed
+    pub fn show_and_wait_timeout(
+        &self,
+        max: Duration,
+    ) -> Result<Option<ClosedReason>, glib::error::Error> {
+        self.show()?;
+
+        let main_loop = glib::MainLoop::new(None, false);
+        let quit = main_loop.clone();
+        let handler = self.connect_closed(move |_, _| quit.quit());
+        let quit = main_loop.clone();
+        let millis = u32::try_from(max.as_millis()).unwrap_or(u32::MAX);
+        let timeout = glib::timeout_add(millis, move || {
+            quit.quit();
+            glib::Continue(true)
+        });
+
+        main_loop.run();
+
+        glib::source_remove(timeout);
+        glib::signal::signal_handler_disconnect(self, handler);
+        Ok(self.get_closed_reason())
+    }
+
+    /// Like `Notification::show`, but falls back to printing the notification
+    /// to stderr if no notification server is running, e.g. on headless
+    /// machines or over SSH. The fallback is plain text: only the summary and
+    /// the body are printed, the icon and the hints are ignored.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if the notification was shown or printed, or `Err(err)` on
+    /// any other error
+    pub fn show_or_print(&self) -> Result<(), glib::error::Error> {
+        match self.show() {
+            Err(ref err) if err.is_service_unknown() => {
+                let summary =
+                    self.get_string_property("summary").unwrap_or_default();
+                match self.get_string_property("body") {
+                    Some(ref body) if !body.is_empty() => {
+                        eprintln!("{}: {}", summary, body)
+                    }
+                    _ => eprintln!("{}", summary),
+                }
+                Ok(())
             }
+            result => result,
         }
     }
 
     /// Sets a hint for `key` with value `value`. If `value` is `None`,
     /// a previously set hint for `key` is unset.
     ///
-    /// If `value` is floating, it is consumed.
+    /// `glib::Variant` never holds a floating reference, values built with
+    /// `ToVariant` are sunk on creation, so libnotify takes its own strong
+    /// reference and `value` can be dropped or reused afterwards. Use
+    /// `Notification::set_hint_variant` for raw, possibly floating
+    /// `GVariant` pointers instead.
     /// ## `key`
     /// the hint key
     /// ## `value`
@@ -121,36 +819,702 @@ impl Notification {
                 gvalue,
             )
         }
+        self.cache_hint(key, value);
     }
 
-    /// Sets the image in the notification from a `gdk_pixbuf::Pixbuf`.
-    /// ## `pixbuf`
-    /// The image.
-    pub fn set_image_from_pixbuf(&self, pixbuf: &gdk_pixbuf::Pixbuf) {
-        assert_initialized_libnotify!();
+    /// Like `Notification::set_hint`, but returns an error instead of
+    /// panicking if `key` contains a nul byte.
+    /// ## `key`
+    /// the hint key
+    /// ## `value`
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or `Err(str)` if `key` contains a nul byte
+    pub fn try_set_hint(
+        &self,
+        key: &str,
+        value: Option<glib::variant::Variant>,
+    ) -> Result<(), String> {
+        check_nul("hint key", key)?;
+        self.set_hint(key, value);
+        Ok(())
+    }
 
-        unsafe {
-            ffi::notify_notification_set_image_from_pixbuf(
-                self.to_glib_none().0,
-                pixbuf.to_glib_none().0,
-            );
-        }
+    /// Like `Notification::try_set_hint`, but checks `value` against the
+    /// expected type of well-known hints listed in `WELL_KNOWN_HINTS`, since
+    /// servers silently ignore hints of the wrong type. Booleans and integers
+    /// are converted to the expected type if the value fits, e.g. an `i32`
+    /// `1` for `transient` becomes `true`.
+    /// ## `key`
+    /// the hint key
+    /// ## `value`
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or `Err(str)` if `key` contains a nul byte or
+    /// `value` cannot be converted to the expected type
+    pub fn set_hint_checked(
+        &self,
+        key: &str,
+        value: glib::variant::Variant,
+    ) -> Result<(), String> {
+        let value = coerce_hint(key, value)?;
+        self.try_set_hint(key, Some(value))
     }
 
-    /// Clears all hints from the notification.
-    pub fn clear_hints(&self) {
-        unsafe {
-            ffi::notify_notification_clear_hints(self.to_glib_none().0);
-        }
+    /// Sets a hint from a string of the form `TYPE:NAME:VALUE`, like the
+    /// `--hint` option of `notify-send`, e.g. `int:value:75` or
+    /// `string:desktop-entry:myapp`. `TYPE` is one of:
+    ///
+    /// * `boolean`: `true` or `false`
+    /// * `byte`: an integer from 0 to 255
+    /// * `double`: a floating point number
+    /// * `int`: a 32-bit signed integer
+    /// * `string`: any text, which may contain further colons
+    /// ## `spec`
+    /// The hint.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or `Err(str)` if `spec` is malformed or contains
+    /// a nul byte
+    pub fn set_hint_str(&self, spec: &str) -> Result<(), String> {
+        check_nul("hint", spec)?;
+        let (key, value) = parse_hint(spec)?;
+        self.try_set_hint(key, Some(value))
     }
 
-    /// Sets the application name for the notification. If this function is
-    /// not called or if `app_name` is `None`, the application name will be
-    /// set from the value used in `notify_init` or overridden with
-    /// `notify_set_app_name`.
-    /// ## `app_name`
-    /// the localised application name
-    pub fn set_app_name<'a, P: Into<Option<&'a str>>>(&self, app_name: P) {
+    /// Sets a hint for `key` from a raw `GVariant`, for hints that cannot be
+    /// expressed with `glib::variant::Variant` yet, such as the structured
+    /// `image-data` hint. If `variant` is null, a previously set hint for
+    /// `key` is unset.
+    ///
+    /// libnotify calls `g_variant_ref_sink` on `variant`: a floating
+    /// reference (as returned by the `g_variant_new_*` constructors) is
+    /// consumed by the notification, while a non-floating reference gains an
+    /// additional reference and remains owned by the caller.
+    /// ## `key`
+    /// the hint key
+    /// ## `variant`
+    /// the raw hint value, or null
+    ///
+    /// # Safety
+    ///
+    /// `variant` must be null or point to a valid `GVariant`.
+    pub unsafe fn set_hint_variant(
+        &self,
+        key: &str,
+        variant: *mut glib_ffi::GVariant,
+    ) {
+        assert_initialized_libnotify!();
+
+        // Sinks a floating `variant` into the cached wrapper, libnotify
+        // then takes its own reference.
+        let value = if variant.is_null() {
+            None
+        } else {
+            Some(from_glib_none(variant))
+        };
+        ffi::notify_notification_set_hint(
+            self.to_glib_none().0,
+            key.to_glib_none().0,
+            variant,
+        );
+        self.cache_hint(key, value);
+    }
+
+    /// Asks the server to keep the notification in its history after it has
+    /// been displayed, or to discard it once it expires. This sets the
+    /// `transient` hint to the inverse of `persistent`.
+    ///
+    /// Only servers advertising the `persistence` capability keep a history
+    /// at all; others ignore the hint.
+    /// ## `persistent`
+    /// Whether the notification should be kept.
+    pub fn set_persistence(&self, persistent: bool) {
+        self.set_hint("transient", Some((!persistent).to_variant()));
+    }
+
+    /// Sets the `x-canonical-private-synchronous` hint, which makes servers
+    /// derived from Ubuntu's notify-osd (and GNOME Shell) replace the previous
+    /// notification with the same `key` instead of stacking them. This is
+    /// meant for OSD-style notifications, e.g. for volume or brightness.
+    ///
+    /// This hint is server-specific and ignored by other servers.
+    /// ## `key`
+    /// The key shared by the notifications that replace each other.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or `Err(str)` if `key` contains a nul byte
+    pub fn set_synchronous(&self, key: &str) -> Result<(), String> {
+        self.set_string_hint("x-canonical-private-synchronous", key)
+    }
+
+    /// Sets the `sound-name` hint, a themeable sound from the freedesktop
+    /// sound naming specification that the server plays when showing the
+    /// notification. Only servers advertising the `sound` capability play
+    /// sounds. See `Notification::set_sound` for common names.
+    /// ## `name`
+    /// The sound name, e.g. `"message-new-instant"`.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or `Err(str)` if `name` contains a nul byte
+    pub fn set_sound_name(&self, name: &str) -> Result<(), String> {
+        self.set_string_hint("sound-name", name)
+    }
+
+    /// Like `Notification::set_sound_name`, but with one of the common
+    /// sound names of `SoundName`.
+    /// ## `sound`
+    /// The sound.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or `Err(str)` if a custom name contains a nul
+    /// byte
+    pub fn set_sound(&self, sound: SoundName) -> Result<(), String> {
+        self.set_sound_name(sound.as_str())
+    }
+
+    /// Sets the `x-kde-urls` hint, a list of URLs that KDE Plasma shows as
+    /// links or previews below the notification, e.g. the files of a
+    /// finished download. The hint is set on every server, but only Plasma
+    /// is known to display it.
+    /// ## `urls`
+    /// The URLs, e.g. `"file:///home/user/Downloads/file.pdf"`.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or `Err(str)` if a URL contains a nul byte
+    pub fn set_kde_urls(&self, urls: &[&str]) -> Result<(), String> {
+        for url in urls {
+            check_nul("URL", url)?;
+        }
+        let value: glib::Variant = unsafe {
+            let strv: Stash<*mut *mut c_char, [&str]> = urls.to_glib_none();
+            from_glib_none(glib_ffi::g_variant_new_strv(
+                strv.0,
+                urls.len() as isize,
+            ))
+        };
+        self.try_set_hint("x-kde-urls", Some(value))
+    }
+
+    /// Sets the `x-dunst-stack-tag` hint, which makes dunst replace a shown
+    /// notification with the same tag instead of showing another one. Other
+    /// servers ignore it, see `Notification::set_group` for a portable
+    /// variant.
+    /// ## `tag`
+    /// The stack tag.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or `Err(str)` if `tag` contains a nul byte
+    pub fn set_dunst_stack_tag(&self, tag: &str) -> Result<(), String> {
+        self.set_string_hint("x-dunst-stack-tag", tag)
+    }
+
+    /// Sets the `x-accessible-text` hint, a plain text version of the
+    /// notification for screen readers to announce instead of the formatted
+    /// summary and body. This hint is not part of the specification and few
+    /// servers or assistive technologies read it, all others ignore it.
+    /// ## `text`
+    /// The text to announce.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or `Err(str)` if `text` contains a nul byte
+    pub fn set_accessible_text(&self, text: &str) -> Result<(), String> {
+        self.set_string_hint("x-accessible-text", text)
+    }
+
+    /// Sets the `x-priority` hint, an integer that servers reading it use to
+    /// order notifications of the same urgency, higher values first. This
+    /// allows finer control of the stacking order than the three urgency
+    /// levels of `Notification::set_urgency`.
+    ///
+    /// The hint is not part of the specification and none of the servers
+    /// recognized by `detect_server()` has a priority hint of its own, so
+    /// the same hint is sent everywhere. Servers that do not know it ignore
+    /// it and order notifications as usual, typically by urgency and age.
+    /// ## `priority`
+    /// The priority, `0` being neutral.
+    pub fn set_priority(&self, priority: i32) {
+        self.set_hint("x-priority", Some(priority.to_variant()));
+    }
+
+    /// Groups the notification with others sharing the same `tag`, so that
+    /// servers collapse them into one instead of stacking them. The hint is
+    /// chosen based on the server identified by `detect_server()`:
+    ///
+    /// * dunst: `x-dunst-stack-tag`
+    /// * notify-osd: `x-canonical-private-synchronous`
+    ///
+    /// On any other server no hint is known, so the notification is not
+    /// grouped and this returns `Ok(())` without setting a hint. With the
+    /// `tracing` feature, a warning naming the server is emitted then.
+    /// ## `tag`
+    /// The tag shared by the grouped notifications.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or `Err(str)` if `tag` contains a nul byte
+    pub fn set_group(&self, tag: &str) -> Result<(), String> {
+        check_nul("hint value", tag)?;
+        match detect_server() {
+            KnownServer::Dunst => {
+                self.set_dunst_stack_tag(tag)
+            }
+            KnownServer::NotifyOsd => self.set_synchronous(tag),
+            _server => {
+                #[cfg(feature = "tracing")]
+                warn!(server = ?_server, "no grouping hint for this server");
+                Ok(())
+            }
+        }
+    }
+
+    /// Asks the server to show the notification on the monitor `index` of
+    /// the default GDK screen, by pointing the `x` and `y` hints at the
+    /// center of that monitor. GTK must have been initialized.
+    ///
+    /// This is best-effort: the positioning hints are optional in the
+    /// specification, and servers that ignore them show the notification
+    /// in their usual place.
+    /// ## `index`
+    /// The monitor number, as used by `gdk::Screen`.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or `Err(str)` if there is no default screen or
+    /// no monitor `index`
+    #[cfg(feature = "gtk")]
+    pub fn set_monitor(&self, index: u32) -> Result<(), String> {
+        use gdk::ScreenExt;
+
+        let screen = gdk::Screen::get_default()
+            .ok_or_else(|| String::from("No default screen"))?;
+        let index = i32::try_from(index).unwrap_or(i32::MAX);
+        if index >= screen.get_n_monitors() {
+            return Err(format!("No monitor {}", index));
+        }
+
+        let geometry = screen.get_monitor_geometry(index);
+        let x = geometry.x + geometry.width / 2;
+        let y = geometry.y + geometry.height / 2;
+        self.set_hint("x", Some(x.to_variant()));
+        self.set_hint("y", Some(y.to_variant()));
+        Ok(())
+    }
+
+    /// Sets the image in the notification from a `gdk_pixbuf::Pixbuf`.
+    /// ## `pixbuf`
+    /// The image.
+    pub fn set_image_from_pixbuf(&self, pixbuf: &gdk_pixbuf::Pixbuf) {
+        assert_initialized_libnotify!();
+
+        unsafe {
+            ffi::notify_notification_set_image_from_pixbuf(
+                self.to_glib_none().0,
+                pixbuf.to_glib_none().0,
+            );
+        }
+    }
+
+    /// Sets the image in the notification from encoded image data in memory,
+    /// e.g. a downloaded avatar, without writing it to a file first. Any
+    /// format supported by the installed gdk-pixbuf loaders can be used,
+    /// typically PNG, JPEG, GIF and BMP among others.
+    /// ## `bytes`
+    /// The encoded image.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or `Err(err)` if the image cannot be decoded
+    pub fn set_image_from_bytes(
+        &self,
+        bytes: &[u8],
+    ) -> Result<(), glib::error::Error> {
+        let loader = gdk_pixbuf::PixbufLoader::new();
+        let written = loader.loader_write(bytes);
+        // The loader must be closed even when writing failed.
+        let closed = loader.close();
+        written.and(closed)?;
+
+        let pixbuf = loader
+            .get_pixbuf()
+            .expect("PixbufLoader closed successfully");
+        self.set_image_from_pixbuf(&pixbuf);
+        Ok(())
+    }
+
+    /// Sets the body of the notification from markup. If the server does not
+    /// advertise the `body-markup` capability, the markup is converted to
+    /// plain text with `strip_markup` first, so that minimal servers show the
+    /// text without any formatting rather than the raw tags.
+    /// ## `markup`
+    /// The body markup.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or `Err(str)` if `markup` contains a nul byte
+    pub fn set_body_markup(&self, markup: &str) -> Result<(), String> {
+        check_nul("body", markup)?;
+        if server_supports("body-markup") {
+            self.set_string_property("body", markup);
+        } else {
+            self.set_string_property("body", &strip_markup(markup));
+        }
+        self.set_markup_enabled(true);
+        Ok(())
+    }
+
+    /// Sets the body of the notification to `text` followed by a link. If
+    /// the server advertises the `body-hyperlinks` capability, the link is
+    /// an anchor showing `link_text`. Otherwise `link_text` is followed by
+    /// the raw `url` in parentheses, so that users can still see it.
+    ///
+    /// All strings are taken as plain text and escaped as needed.
+    /// ## `text`
+    /// The text before the link.
+    /// ## `url`
+    /// The link target.
+    /// ## `link_text`
+    /// The text of the link.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or `Err(str)` if a string contains a nul byte
+    pub fn set_body_with_link(
+        &self,
+        text: &str,
+        url: &str,
+        link_text: &str,
+    ) -> Result<(), String> {
+        check_nul("body", text)?;
+        check_nul("link", url)?;
+        check_nul("link text", link_text)?;
+
+        let caps = get_server_caps();
+        let has_cap = |cap: &str| caps.iter().any(|c| c == cap);
+        let body = link_body(
+            text,
+            url,
+            link_text,
+            has_cap("body-hyperlinks"),
+            has_cap("body-markup"),
+        );
+        self.set_string_property("body", &body);
+        self.set_markup_enabled(true);
+        Ok(())
+    }
+
+    /// Replaces the image of the notification with `pixbuf` and shows the
+    /// notification again, e.g. to update album artwork. Servers that
+    /// support replacing notifications update the image in place, others
+    /// may display a new notification.
+    /// ## `pixbuf`
+    /// The new image.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or `Err(err)` on error
+    pub fn update_image_from_pixbuf(
+        &self,
+        pixbuf: &gdk_pixbuf::Pixbuf,
+    ) -> Result<(), glib::error::Error> {
+        self.set_image_from_pixbuf(pixbuf);
+        self.show()
+    }
+
+    /// Removes the icon and any image from the notification, so that it is
+    /// rendered without one the next time it is shown. This is useful when a
+    /// notification is reused, since `update` with a `None` icon keeps the
+    /// previous icon on some servers.
+    pub fn clear_icon(&self) {
+        self.set_string_property("icon-name", "");
+        for key in &[
+            "image-data",
+            "image_data",
+            "image-path",
+            "image_path",
+            "icon_data",
+        ]
+        {
+            self.set_hint(key, None);
+        }
+    }
+
+    /// Sets the image of the notification to `source` and removes any other
+    /// icon or image, so that the server cannot prefer a previously set one,
+    /// see `ImageSource`.
+    /// ## `source`
+    /// The image.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or `Err(str)` if the icon name or path contains
+    /// a nul byte or the path is not valid UTF-8
+    pub fn set_primary_image(&self, source: ImageSource) -> Result<(), String> {
+        match source {
+            ImageSource::ThemeIcon(name) => {
+                check_nul("icon", &name)?;
+                self.clear_icon();
+                self.set_string_property("icon-name", &name);
+            }
+            ImageSource::File(path) => {
+                let path = path.to_str().ok_or_else(|| {
+                    String::from("Image path is not valid UTF-8")
+                })?;
+                check_nul("image path", path)?;
+                self.clear_icon();
+                self.set_string_hint("image-path", path)?;
+            }
+            ImageSource::Data(pixbuf) => {
+                self.clear_icon();
+                self.set_image_from_pixbuf(&pixbuf);
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds an action to the notification. When the action is invoked by the
+    /// user, `f` is called with the notification and the action key. This
+    /// requires a running main loop and a server advertising the `actions`
+    /// capability.
+    /// ## `action`
+    /// The action key, passed to `f`.
+    /// ## `label`
+    /// The human-readable action label.
+    /// ## `f`
+    /// The callback.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(handle)` with a handle for `Notification::remove_action` on
+    /// success, or `Err(str)` if `action` or `label` contains a nul byte
+    pub fn add_action<F: Fn(&Notification, &str) + 'static>(
+        &self,
+        action: &str,
+        label: &str,
+        f: F,
+    ) -> Result<ActionHandle, String> {
+        check_nul("action", action)?;
+        check_nul("action label", label)?;
+        Ok(self.push_action(action, label, Rc::new(f)))
+    }
+
+    /// Adds an action whose key and label are known to be free of nul
+    /// bytes.
+    fn push_action(
+        &self,
+        action: &str,
+        label: &str,
+        callback: ActionCallback,
+    ) -> ActionHandle {
+        self.register_action(action, label, &callback);
+
+        let mut state = self.state().borrow_mut();
+        state.next_action += 1;
+        let handle = ActionHandle(state.next_action);
+        state.actions.push(RegisteredAction {
+            handle,
+            key: action.to_owned(),
+            label: label.to_owned(),
+            callback,
+        });
+        handle
+    }
+
+    /// Removes a single action added with `Notification::add_action`.
+    /// libnotify can only clear all actions, so the remaining actions are
+    /// cleared and added again, which costs one allocation per remaining
+    /// action. As with adding actions, the change is only displayed after a
+    /// call to `Notification::show`.
+    /// ## `handle`
+    /// The handle returned when adding the action.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the action was removed, `false` if it had already been
+    /// removed.
+    pub fn remove_action(&self, handle: ActionHandle) -> bool {
+        let remaining = {
+            let mut state = self.state().borrow_mut();
+            let len = state.actions.len();
+            state.actions.retain(|action| action.handle != handle);
+            if state.actions.len() == len {
+                return false;
+            }
+            state
+                .actions
+                .iter()
+                .map(|action| {
+                    (
+                        action.key.clone(),
+                        action.label.clone(),
+                        action.callback.clone(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        unsafe {
+            ffi::notify_notification_clear_actions(self.to_glib_none().0);
+        }
+        for (key, label, callback) in &remaining {
+            self.register_action(key, label, callback);
+        }
+        true
+    }
+
+    /// Like `Notification::add_action`, but fails if the server does not
+    /// advertise the `actions` capability, instead of adding an action that
+    /// is never displayed. This lets applications fall back to another
+    /// interaction early.
+    /// ## `action`
+    /// The action key, passed to `f`.
+    /// ## `label`
+    /// The human-readable action label.
+    /// ## `f`
+    /// The callback.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(handle)` with a handle for `Notification::remove_action` on
+    /// success, `Err(ActionError::NulByte)` if `action` or `label` contains
+    /// a nul byte, or `Err(ActionError::Unsupported)` if the server does not
+    /// support actions
+    pub fn try_add_action<F: Fn(&Notification, &str) + 'static>(
+        &self,
+        action: &str,
+        label: &str,
+        f: F,
+    ) -> Result<ActionHandle, ActionError> {
+        if action.contains('\0') {
+            return Err(ActionError::NulByte("action"));
+        }
+        if label.contains('\0') {
+            return Err(ActionError::NulByte("action label"));
+        }
+        if !server_supports("actions") {
+            return Err(UnsupportedFeature::new("actions").into());
+        }
+        Ok(self.push_action(action, label, Rc::new(f)))
+    }
+
+    /// Adds an inline reply field to the notification, as used by chat
+    /// applications, and calls `f` with the text the user sends. This uses
+    /// the KDE extension that Plasma implements: the server must advertise
+    /// the `inline-reply` capability, the `x-kde-reply-placeholder-text`
+    /// hint holds `placeholder`, and the server emits the
+    /// `NotificationReplied` signal on the session bus with the reply. An
+    /// `inline-reply` action labeled "Reply" is added as well, which such
+    /// servers require and others may display as a plain button.
+    ///
+    /// The reply is delivered by the thread-default main context of the
+    /// calling thread, so a main loop must be running there. Adding another
+    /// inline reply replaces this one, and `Notification::clear_actions`
+    /// removes it.
+    /// ## `placeholder`
+    /// The placeholder text of the reply field.
+    /// ## `f`
+    /// The callback.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, `Err(ActionError::NulByte)` if `placeholder`
+    /// contains a nul byte, `Err(ActionError::Unsupported)` if the server
+    /// does not support inline replies, or `Err(ActionError::NoSessionBus)`
+    /// if the session bus cannot be reached
+    pub fn add_inline_reply<F: FnMut(&str) + 'static>(
+        &self,
+        placeholder: &str,
+        mut f: F,
+    ) -> Result<(), ActionError> {
+        if placeholder.contains('\0') {
+            return Err(ActionError::NulByte("placeholder"));
+        }
+        if !server_supports("inline-reply") {
+            return Err(UnsupportedFeature::new("inline-reply").into());
+        }
+        let weak = self.downgrade();
+        let subscription = subscribe_replies(move |id, text| {
+            let ours = weak.upgrade().is_some_and(|notification| {
+                notification.get_id() == id as i32
+            });
+            if ours {
+                f(text)
+            }
+        }).ok_or(ActionError::NoSessionBus)?;
+
+        self.set_hint(
+            "x-kde-reply-placeholder-text",
+            Some(placeholder.to_variant()),
+        );
+        if !self.action_keys().iter().any(|key| key == "inline-reply") {
+            self.push_action("inline-reply", "Reply", Rc::new(|_, _| {}));
+        }
+        self.state().borrow_mut().reply = Some(subscription);
+        Ok(())
+    }
+
+    /// Gets the keys of the actions added with `Notification::add_action`.
+    ///
+    /// # Returns
+    ///
+    /// The action keys, in the order they were added.
+    pub fn action_keys(&self) -> Vec<String> {
+        self.state()
+            .borrow()
+            .actions
+            .iter()
+            .map(|action| action.key.clone())
+            .collect()
+    }
+
+    /// Gets the actions added with `Notification::add_action`.
+    ///
+    /// # Returns
+    ///
+    /// The `(key, label)` pairs, in the order they were added.
+    pub fn actions(&self) -> Vec<(String, String)> {
+        self.state()
+            .borrow()
+            .actions
+            .iter()
+            .map(|action| (action.key.clone(), action.label.clone()))
+            .collect()
+    }
+
+    /// Clears all actions from the notification.
+    pub fn clear_actions(&self) {
+        unsafe {
+            ffi::notify_notification_clear_actions(self.to_glib_none().0);
+        }
+        let mut state = self.state().borrow_mut();
+        state.actions.clear();
+        state.reply = None;
+    }
+
+    /// Clears all hints from the notification.
+    pub fn clear_hints(&self) {
+        unsafe {
+            ffi::notify_notification_clear_hints(self.to_glib_none().0);
+        }
+        self.state().borrow_mut().hints.clear();
+    }
+
+    /// Sets the application name for the notification. If this function is
+    /// not called or if `app_name` is `None`, the application name will be
+    /// set from the value used in `notify_init` or overridden with
+    /// `notify_set_app_name`.
+    /// ## `app_name`
+    /// the localised application name
+    pub fn set_app_name<'a, P: Into<Option<&'a str>>>(&self, app_name: P) {
         let app_name = app_name.into();
         let app_name = app_name.to_glib_none();
         unsafe {
@@ -172,22 +1536,117 @@ impl Notification {
                 category.to_glib_none().0,
             );
         }
+        self.cache_hint("category", Some(category.to_variant()));
     }
 
-    /// Sets the timeout of the notification. To set the default time, pass
-    /// `NOTIFY_EXPIRES_DEFAULT` as `timeout`. To set the notification to never
-    /// expire, pass `NOTIFY_EXPIRES_NEVER`.
+    /// Like `Notification::set_category`, but takes a `Category`.
+    /// ## `category`
+    /// The category.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or `Err(str)` if a `Category::Custom` contains a
+    /// nul byte
+    pub fn set_category_kind(&self, category: Category) -> Result<(), String> {
+        check_nul("category", category.as_str())?;
+        self.set_category(category.as_str());
+        Ok(())
+    }
+
+    /// Sets the category of this notification together with the urgency
+    /// conventionally used for it, as listed for `Category::default_urgency`.
+    /// A later call to `Notification::set_urgency` overrides the urgency.
+    /// ## `category`
+    /// The category.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or `Err(str)` if a `Category::Custom` contains a
+    /// nul byte
+    pub fn apply_category_defaults(
+        &self,
+        category: Category,
+    ) -> Result<(), String> {
+        check_nul("category", category.as_str())?;
+        self.set_urgency(category.default_urgency());
+        self.set_category_kind(category)
+    }
+
+    /// Sets the category of this notification, and its icon to the one
+    /// listed for `Category::default_icon` if no icon is set yet. An icon
+    /// set before keeps precedence, so the mapping can be overridden.
+    /// ## `category`
+    /// The category.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or `Err(str)` if a `Category::Custom` contains a
+    /// nul byte
+    pub fn apply_category_icon(
+        &self,
+        category: Category,
+    ) -> Result<(), String> {
+        check_nul("category", category.as_str())?;
+        let has_icon = self
+            .get_string_property("icon-name")
+            .is_some_and(|icon| !icon.is_empty());
+        if let Some(icon) = category.default_icon().filter(|_| !has_icon) {
+            self.set_string_property("icon-name", icon);
+        }
+        self.set_category_kind(category)
+    }
+
+    /// Sets the timeout of the notification, either as a `Timeout` or in
+    /// milliseconds. To set the default time, pass `Timeout::Default` or
+    /// `EXPIRES_DEFAULT` as `timeout`. To set the notification to never
+    /// expire, pass `Timeout::Never` or `EXPIRES_NEVER`.
     ///
     /// Note that the timeout may be ignored by the server.
     /// ## `timeout`
-    /// The timeout in milliseconds.
-    pub fn set_timeout(&self, timeout: i32) {
+    /// The timeout.
+    pub fn set_timeout<T: Into<Timeout>>(&self, timeout: T) {
+        let timeout = timeout.into();
         unsafe {
             ffi::notify_notification_set_timeout(
                 self.to_glib_none().0,
-                timeout,
+                timeout.into(),
             );
         }
+        self.state().borrow_mut().timeout = timeout;
+    }
+
+    /// Sets the timeout to the time needed to read the summary and the body
+    /// at `wpm` words per minute. The timeout in milliseconds is
+    /// `1000 + 60000 * words / wpm`, clamped to 3 to 30 seconds, where words
+    /// are separated by whitespace and markup in the body is not counted.
+    /// Call this after the text has been set.
+    /// ## `wpm`
+    /// The reading speed in words per minute, e.g. 200. `0` counts as 1.
+    pub fn set_timeout_for_reading(&self, wpm: u32) {
+        let summary = self.get_string_property("summary").unwrap_or_default();
+        let body = self.get_string_property("body").unwrap_or_default();
+        let timeout = reading_timeout_ms(&summary, &body, wpm);
+        self.set_timeout(Timeout::Milliseconds(timeout as i32));
+    }
+
+    /// Leaves the expiration of the notification to the server, which is
+    /// the same as `set_timeout(Timeout::Default)`.
+    pub fn use_server_timeout(&self) {
+        self.set_timeout(Timeout::Default);
+    }
+
+    /// Like `Notification::set_timeout`, but returns the notification so
+    /// that calls can be chained, e.g.
+    /// `notification.timeout(Timeout::Never).show()`.
+    /// ## `timeout`
+    /// The timeout.
+    ///
+    /// # Returns
+    ///
+    /// The notification.
+    pub fn timeout<T: Into<Timeout>>(&self, timeout: T) -> &Self {
+        self.set_timeout(timeout);
+        self
     }
 
     /// Sets the urgency level of this notification.
@@ -202,6 +1661,100 @@ impl Notification {
                 urgency.to_glib(),
             );
         }
+        let level = urgency.to_glib() as u8;
+        self.cache_hint("urgency", Some(level.to_variant()));
+    }
+
+    /// Applies `cfg` to the notification with as few calls into libnotify as
+    /// possible: the text and icon are set with a single `update`, followed
+    /// by the urgency, the timeout and the hints. This is cheaper than the
+    /// individual setters in hot loops. As with `update`, the change is only
+    /// displayed after a call to `Notification::show`.
+    ///
+    /// All strings are checked up front, so nothing is changed on error.
+    /// ## `cfg`
+    /// The configuration.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or `Err(str)` if a string contains a nul byte
+    pub fn configure(&self, cfg: &NotificationConfig) -> Result<(), String> {
+        check_nul("summary", &cfg.summary)?;
+        check_nul("body", cfg.body.as_deref().unwrap_or(""))?;
+        check_nul("icon", cfg.icon.as_deref().unwrap_or(""))?;
+        for (key, _) in &cfg.hints {
+            check_nul("hint key", key)?;
+        }
+
+        self.update(&cfg.summary, cfg.body.as_deref(), cfg.icon.as_deref())?;
+        if let Some(urgency) = cfg.urgency {
+            self.set_urgency(urgency);
+        }
+        if let Some(timeout) = cfg.timeout {
+            self.set_timeout(timeout);
+        }
+        for (key, value) in &cfg.hints {
+            self.set_hint(key, Some(value.clone()));
+        }
+        Ok(())
+    }
+
+    /// Raises the urgency of the notification by one level and shows it
+    /// again, e.g. for a warning that becomes critical when it is not
+    /// addressed. Low urgency becomes normal, and normal urgency, which is
+    /// also the default when none was set, becomes critical. A critical
+    /// notification is shown again unchanged.
+    ///
+    /// Servers commonly ignore the timeout of critical notifications and
+    /// keep them until they are dismissed.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or `Err(err)` on error
+    pub fn escalate(&self) -> Result<(), glib::error::Error> {
+        let urgency = self.cached_urgency().unwrap_or(Urgency::Normal);
+        self.set_urgency(next_urgency(urgency));
+        self.show()
+    }
+
+    /// Sets the urgency level depending on whether the user is present:
+    /// `idle` if the screensaver is active and `active` otherwise, so that
+    /// e.g. notifications piling up while the user is away stay until they
+    /// return. Presence is queried once, from the
+    /// `org.freedesktop.ScreenSaver` service on the session bus, which KDE
+    /// Plasma, Xfce and others provide. Without that service, `active` is
+    /// used.
+    /// ## `active`
+    /// The urgency level if the user is present.
+    /// ## `idle`
+    /// The urgency level if the user is away.
+    pub fn set_urgency_with_presence(&self, active: Urgency, idle: Urgency) {
+        match screensaver_active() {
+            Some(true) => self.set_urgency(idle),
+            _ => self.set_urgency(active),
+        }
+    }
+
+    /// Gets the urgency level last set through this crate, e.g. with
+    /// `Notification::set_urgency` or `Notification::apply_category_defaults`.
+    /// libnotify cannot report the urgency, so this does not reflect what
+    /// the server made of it.
+    ///
+    /// # Returns
+    ///
+    /// The urgency level, or `Urgency::Normal` if none was set.
+    pub fn urgency(&self) -> Urgency {
+        self.cached_urgency().unwrap_or(Urgency::Normal)
+    }
+
+    /// Whether the urgency level is `Urgency::Critical`, see
+    /// `Notification::urgency`.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the notification is critical, `false` otherwise.
+    pub fn is_critical(&self) -> bool {
+        self.urgency() == Urgency::Critical
     }
 
     /// Updates the notification text and icon. This won't send the update out
@@ -216,7 +1769,9 @@ impl Notification {
     ///
     /// # Returns
     ///
-    /// `Ok(())` on success, or `Err(str)` if an invalid parameter was passed
+    /// `Ok(())` on success, or `Err(str)` if a string contains a nul byte or
+    /// libnotify rejected the update. The latter error names the lengths of
+    /// the strings, since libnotify does not give a reason.
     // TODO: switch back to BoolError when it hits stable glib
     pub fn update<
         'a,
@@ -230,22 +1785,871 @@ impl Notification {
         icon: Q,
     ) -> Result<(), String> {
         let body = body.into();
-        let body = body.to_glib_none();
         let icon = icon.into();
-        let icon = icon.to_glib_none();
-        unsafe {
-            let b = ffi::notify_notification_update(
+        check_update(summary, body, icon)?;
+
+        let b = unsafe {
+            ffi::notify_notification_update(
                 self.to_glib_none().0,
                 summary.to_glib_none().0,
-                body.0,
-                icon.0,
+                body.to_glib_none().0,
+                icon.to_glib_none().0,
+            )
+        };
+        match b {
+            glib_ffi::GFALSE => Err(rejected_update(summary, body, icon)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Gets the id the notification server assigned to the notification.
+    ///
+    /// # Returns
+    ///
+    /// The id, or `0` if the notification has not been shown yet.
+    pub fn get_id(&self) -> i32 {
+        self.get_property("id")
+            .ok()
+            .and_then(|value| value.get::<i32>())
+            .unwrap_or(0)
+    }
+
+    /// Sets the id of the notification, so that the next
+    /// `Notification::show` replaces the notification that the server knows
+    /// under `id`, even one shown through another `Notification`. If the
+    /// server does not know `id`, e.g. because that notification was closed,
+    /// it shows a new notification with a new id instead. Setting `0` makes
+    /// the next show create a new notification.
+    /// ## `id`
+    /// The id, as returned by `Notification::get_id`.
+    pub fn set_replaces_id(&self, id: u32) {
+        // libnotify stores the D-Bus uint32 id as a gint.
+        self.set_property("id", &(id as i32).to_value())
+            .expect("NotifyNotification id property");
+    }
+
+    /// Gets the reason the notification was closed.
+    ///
+    /// # Returns
+    ///
+    /// The reason, or `None` if the notification has not been closed.
+    pub fn get_closed_reason(&self) -> Option<ClosedReason> {
+        let reason = unsafe {
+            ffi::notify_notification_get_closed_reason(self.to_glib_none().0)
+        };
+
+        match reason {
+            -1 => None,
+            reason => Some(from_glib(reason)),
+        }
+    }
+
+    /// Connects to the `closed` signal, which is emitted when the
+    /// notification is closed. This requires a running main loop.
+    ///
+    /// Since the callback receives the notification, one callback can be
+    /// shared by several notifications and tell them apart, e.g. by
+    /// `Notification::get_id`.
+    /// ## `f`
+    /// The callback, receiving the closed notification and the reason it was
+    /// closed.
+    ///
+    /// # Returns
+    ///
+    /// The handler id, for use with `glib::signal::signal_handler_disconnect`.
+    pub fn connect_closed<F: Fn(&Notification, ClosedReason) + 'static>(
+        &self,
+        f: F,
+    ) -> u64 {
+        unsafe {
+            let f: Box<ClosedCallback> = Box::new(Box::new(f));
+            let trampoline: ClosedTrampoline = closed_trampoline;
+            let this: *mut ffi::NotifyNotification = self.to_glib_none().0;
+            glib::signal::connect(
+                this as *mut gobject_ffi::GObject,
+                "closed",
+                Some(mem::transmute::<ClosedTrampoline, unsafe extern "C" fn()>(
+                    trampoline,
+                )),
+                Box::into_raw(f) as *mut _,
+            )
+        }
+    }
+
+    /// Updates the summary and the body of the notification, keeping its
+    /// icon. Unlike `update(summary, Some(body), None)`, which removes the
+    /// icon, the current icon is passed on again. As with `update`, the
+    /// change is only displayed after a call to `Notification::show`.
+    /// ## `summary`
+    /// The new summary text.
+    /// ## `body`
+    /// The new body text.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or `Err(str)` if a string contains a nul byte or
+    /// an invalid parameter was passed
+    pub fn set_text(&self, summary: &str, body: &str) -> Result<(), String> {
+        check_nul("summary", summary)?;
+        check_nul("body", body)?;
+        let icon = self.get_string_property("icon-name");
+        self.update(summary, body, icon.as_deref())
+    }
+
+    /// Sets the summary of the notification, shortened to at most
+    /// `max_chars` characters for servers that misrender long summaries.
+    /// Longer summaries are cut at a character boundary and end in an
+    /// ellipsis (`…`), which counts towards `max_chars`. Note that this
+    /// counts `char`s, not bytes or grapheme clusters, so a character made
+    /// of several code points may still be split. Other setters send the
+    /// summary unchanged.
+    /// ## `summary`
+    /// The new summary text.
+    /// ## `max_chars`
+    /// The maximum number of characters.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or `Err(str)` if `summary` contains a nul byte
+    pub fn set_summary_truncated(
+        &self,
+        summary: &str,
+        max_chars: usize,
+    ) -> Result<(), String> {
+        check_nul("summary", summary)?;
+        self.set_string_property(
+            "summary",
+            &truncate_text(summary, max_chars),
+        );
+        Ok(())
+    }
+
+    /// Sets the body of the notification to `text` with line breaks inserted
+    /// at word boundaries, so that no line is longer than `width` characters,
+    /// for minimal servers that do not wrap long lines themselves. Existing
+    /// line breaks are kept, while other runs of whitespace become a single
+    /// space. Words are never split, so a word longer than `width` gets a
+    /// line of its own.
+    ///
+    /// `width` counts `char`s, which matches the visual width only for
+    /// text without wide or combining characters. Servers that render the
+    /// body as markup, or wrap it themselves, may lay it out differently.
+    /// ## `text`
+    /// The body text.
+    /// ## `width`
+    /// The maximum line width, in characters.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or `Err(str)` if `text` contains a nul byte
+    pub fn set_body_wrapped(
+        &self,
+        text: &str,
+        width: usize,
+    ) -> Result<(), String> {
+        check_nul("body", text)?;
+        self.set_string_property("body", &wrap_text(text, width));
+        Ok(())
+    }
+
+    /// Connects a callback that is only invoked when the notification is
+    /// closed because it expired, as opposed to being dismissed or closed by
+    /// the application, e.g. to re-arm a reminder that went unacknowledged.
+    /// This requires a running main loop.
+    /// ## `f`
+    /// The callback.
+    ///
+    /// # Returns
+    ///
+    /// The handler id, for use with `glib::signal::signal_handler_disconnect`.
+    pub fn on_expired<F: FnMut() + 'static>(&self, f: F) -> u64 {
+        let f = RefCell::new(f);
+        self.connect_closed(move |_, reason| {
+            if reason == ClosedReason::Expired {
+                (*f.borrow_mut())()
+            }
+        })
+    }
+
+    /// Reports progress on the notification, typically for a download or
+    /// another long running task. This sets the `value` hint to `percent`,
+    /// replaces the body with the percentage and shows the notification
+    /// again. Since the same notification is reused, servers that support
+    /// replacement update it in place instead of stacking a new pop-up.
+    /// ## `percent`
+    /// The progress, clamped to `0..=100`.
+    /// ## `auto_close`
+    /// Whether to close the notification once `percent` reaches 100.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or `Err(err)` on error
+    pub fn update_progress(
+        &self,
+        percent: i32,
+        auto_close: bool,
+    ) -> Result<(), glib::error::Error> {
+        let percent = percent.clamp(0, 100);
+        self.set_hint("value", Some(percent.to_variant()));
+        self.set_string_property("body", &format!("{}%", percent));
+        self.show()?;
+
+        if auto_close && percent == 100 {
+            self.close()?;
+        }
+        Ok(())
+    }
+
+    /// Shows a notification with `summary` and drives it through the
+    /// `(percent, label)` pairs of `stream`, updating it in place with the
+    /// `value` hint and `label` as the body, and closes it once `stream` is
+    /// exhausted. This blocks while consuming `stream`, so it suits
+    /// synchronous work that yields its progress, e.g. an iterator over
+    /// processed chunks.
+    /// ## `summary`
+    /// The required summary text.
+    /// ## `stream`
+    /// The progress, clamped to `0..=100`, and the body to show with it.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` on success, or the first `Err(err)` on error, with the code
+    /// `G_IO_ERROR_INVALID_ARGUMENT` if `summary` or a label contains a nul
+    /// byte. The notification is closed before an invalid label is reported.
+    pub fn show_progress_stream<I: IntoIterator<Item = (i32, String)>>(
+        summary: &str,
+        stream: I,
+    ) -> Result<(), glib::error::Error> {
+        assert_initialized_libnotify!();
+        check_nul("summary", summary).map_err(|err| invalid_argument(&err))?;
+        let notification = Notification::new(summary, None, None);
+        for (percent, label) in stream {
+            if let Err(err) = check_nul("body", &label) {
+                if notification.get_id() != 0 {
+                    notification.close()?;
+                }
+                return Err(invalid_argument(&err));
+            }
+            let percent = percent.clamp(0, 100);
+            notification.set_hint("value", Some(percent.to_variant()));
+            notification.set_string_property("body", &label);
+            notification.show()?;
+        }
+
+        if notification.get_id() != 0 {
+            notification.close()?;
+        }
+        Ok(())
+    }
+
+    /// Takes a snapshot of the state of the notification as far as this
+    /// crate tracks it, for troubleshooting actions or hints that do not
+    /// behave as expected.
+    ///
+    /// # Returns
+    ///
+    /// The snapshot, best read with its `Debug` output.
+    pub fn debug_state(&self) -> NotificationDebug {
+        let state = self.state().borrow();
+        NotificationDebug {
+            id: self.get_id(),
+            summary: self.get_string_property("summary"),
+            body: self.get_string_property("body"),
+            icon: self.get_string_property("icon-name"),
+            urgency: self.cached_urgency(),
+            timeout: state.timeout,
+            markup: state.markup,
+            hints: state.hints.clone(),
+            actions: state
+                .actions
+                .iter()
+                .map(|action| (action.key.clone(), action.label.clone()))
+                .collect(),
+        }
+    }
+
+    /// Whether the notification has been shown and not closed since.
+    fn is_shown(&self) -> bool {
+        self.get_id() != 0 && self.get_closed_reason().is_none()
+    }
+
+    /// The Rust-side state of the notification, shared by all wrappers of
+    /// the same `NotifyNotification`.
+    fn state(&self) -> &RefCell<NotificationState> {
+        unsafe {
+            let this: *mut ffi::NotifyNotification = self.to_glib_none().0;
+            let this = this as *mut gobject_ffi::GObject;
+            let key = STATE_KEY.as_ptr() as *const _;
+            let mut state = gobject_ffi::g_object_get_data(this, key);
+            if state.is_null() {
+                let new: Box<RefCell<NotificationState>> = Box::default();
+                state = Box::into_raw(new) as glib_ffi::gpointer;
+                gobject_ffi::g_object_set_data_full(
+                    this,
+                    key,
+                    state,
+                    Some(destroy_state),
+                );
+            }
+            &*(state as *const RefCell<NotificationState>)
+        }
+    }
+
+    /// The hints set on the notification through this crate, in the order
+    /// they were first set. Hints libnotify sets on its own, such as the
+    /// image data of `Notification::set_image_from_pixbuf`, are not included.
+    pub(crate) fn cached_hints(&self) -> Vec<(String, glib::Variant)> {
+        self.state().borrow().hints.clone()
+    }
+
+    fn register_action(&self, key: &str, label: &str, f: &ActionCallback) {
+        unsafe {
+            let f: Box<ActionCallback> = Box::new(f.clone());
+            notify_notification_add_action(
+                self.to_glib_none().0,
+                key.to_glib_none().0,
+                label.to_glib_none().0,
+                Some(action_trampoline),
+                Box::into_raw(f) as glib_ffi::gpointer,
+                Some(destroy_action),
             );
-            match b {
-                glib_ffi::GFALSE => Err(
-                    String::from("Invalid parameter passed"),
-                ),
-                _ => Ok(()),
+        }
+    }
+
+    /// The urgency last set through this crate, if any.
+    pub(crate) fn cached_urgency(&self) -> Option<Urgency> {
+        let state = self.state().borrow();
+        let level = state
+            .hints
+            .iter()
+            .find(|(key, _)| key == "urgency")
+            .and_then(|(_, value)| value.get::<u8>())?;
+        Some(match level {
+            0 => Urgency::Low,
+            1 => Urgency::Normal,
+            2 => Urgency::Critical,
+            level => Urgency::__Unknown(i32::from(level)),
+        })
+    }
+
+    fn cache_hint(&self, key: &str, value: Option<glib::Variant>) {
+        let hints = &mut self.state().borrow_mut().hints;
+        match (hints.iter().position(|(k, _)| k == key), value) {
+            (Some(i), Some(value)) => hints[i].1 = value,
+            (Some(i), None) => {
+                hints.remove(i);
+            }
+            (None, Some(value)) => hints.push((key.to_owned(), value)),
+            (None, None) => (),
+        }
+    }
+
+    fn set_string_hint(&self, key: &str, value: &str) -> Result<(), String> {
+        check_nul("hint value", value)?;
+        self.try_set_hint(key, Some(value.to_variant()))
+    }
+
+    pub(crate) fn get_string_property(&self, name: &str) -> Option<String> {
+        self.get_property(name).ok().and_then(|value| value.get())
+    }
+
+    fn set_string_property(&self, name: &str, value: &str) {
+        self.set_property(name, &value.to_value())
+            .expect("NotifyNotification string property");
+    }
+}
+
+
+const STATE_KEY: &[u8] = b"rust-libnotify-state\0";
+
+#[derive(Default)]
+struct NotificationState {
+    /// The timeout last set through this crate.
+    timeout: Timeout,
+    /// Whether the body is markup, see `Notification::set_markup_enabled`.
+    markup: bool,
+    actions: Vec<RegisteredAction>,
+    /// The last `ActionHandle` handed out.
+    next_action: u64,
+    hints: Vec<(String, glib::Variant)>,
+    /// `Some` during a batch, `true` once a show was deferred.
+    batch: Option<bool>,
+    /// When the notification was last shown, for the action latency.
+    #[cfg(feature = "tracing")]
+    shown_at: Option<Instant>,
+    /// The subscription of `Notification::add_inline_reply`.
+    reply: Option<ReplySubscription>,
+}
+
+unsafe extern "C" fn destroy_state(state: glib_ffi::gpointer) {
+    let _guard = glib::CallbackGuard::new();
+    drop(Box::from_raw(state as *mut RefCell<NotificationState>));
+}
+
+type ActionCallback = Rc<dyn Fn(&Notification, &str) + 'static>;
+
+struct RegisteredAction {
+    handle: ActionHandle,
+    key: String,
+    label: String,
+    callback: ActionCallback,
+}
+
+extern "C" {
+    // Commented out in the generated libnotify-sys bindings, because gir
+    // reports a `GFreeFunc`/`GDestroyNotify` mismatch for `free_func`. The
+    // two function pointer types are ABI compatible.
+    fn notify_notification_add_action(
+        notification: *mut ffi::NotifyNotification,
+        action: *const c_char,
+        label: *const c_char,
+        callback: ffi::NotifyActionCallback,
+        user_data: glib_ffi::gpointer,
+        free_func: glib_ffi::GDestroyNotify,
+    );
+}
+
+unsafe extern "C" fn action_trampoline(
+    this: *mut ffi::NotifyNotification,
+    action: *mut c_char,
+    f: glib_ffi::gpointer,
+) {
+    let _guard = glib::CallbackGuard::new();
+    // Keeps the callback alive if it removes its own action.
+    let f = (*(f as *const ActionCallback)).clone();
+    let action: String = from_glib_none(action as *const c_char);
+    let this: Notification = from_glib_borrow(this);
+    #[cfg(feature = "tracing")]
+    {
+        let since_show = this.state().borrow().shown_at.map(|shown_at| {
+            shown_at.elapsed().as_millis() as u64
+        });
+        debug!(
+            id = this.get_id(),
+            summary = this.get_string_property("summary").as_deref(),
+            action = action.as_str(),
+            since_show_ms = since_show,
+            "action invoked"
+        );
+    }
+    f(&this, &action)
+}
+
+unsafe extern "C" fn destroy_action(f: glib_ffi::gpointer) {
+    let _guard = glib::CallbackGuard::new();
+    drop(Box::from_raw(f as *mut ActionCallback));
+}
+
+type ClosedCallback = Box<dyn Fn(&Notification, ClosedReason) + 'static>;
+
+type ClosedTrampoline = unsafe extern "C" fn(
+    *mut ffi::NotifyNotification,
+    glib_ffi::gpointer,
+);
+
+unsafe extern "C" fn closed_trampoline(
+    this: *mut ffi::NotifyNotification,
+    f: glib_ffi::gpointer,
+) {
+    let _guard = glib::CallbackGuard::new();
+    let f = &*(f as *const ClosedCallback);
+    let notification: Notification = from_glib_borrow(this);
+    let reason = notification
+        .get_closed_reason()
+        .unwrap_or(ClosedReason::Undefined);
+    f(&notification, reason)
+}
+
+/// Fails with a descriptive error if `value` contains a nul byte, which
+/// cannot be passed to libnotify.
+pub(crate) fn check_nul(what: &str, value: &str) -> Result<(), String> {
+    if value.contains('\0') {
+        Err(format!("Nul byte in {}", what))
+    } else {
+        Ok(())
+    }
+}
+
+/// Checks the arguments of `Notification::update` for nul bytes.
+fn check_update(
+    summary: &str,
+    body: Option<&str>,
+    icon: Option<&str>,
+) -> Result<(), String> {
+    check_nul("summary", summary)?;
+    check_nul("body", body.unwrap_or(""))?;
+    check_nul("icon", icon.unwrap_or(""))
+}
+
+/// The error of `Notification::update` when libnotify rejects the update.
+fn rejected_update(
+    summary: &str,
+    body: Option<&str>,
+    icon: Option<&str>,
+) -> String {
+    let len = |value: Option<&str>| {
+        value.map_or(String::from("none"), |value| {
+            format!("{} bytes", value.len())
+        })
+    };
+    format!(
+        "Invalid parameter passed (summary: {} bytes, body: {}, icon: {})",
+        summary.len(),
+        len(body),
+        len(icon),
+    )
+}
+
+/// The urgency `Notification::escalate` raises `urgency` to.
+fn next_urgency(urgency: Urgency) -> Urgency {
+    match urgency {
+        Urgency::Low => Urgency::Normal,
+        _ => Urgency::Critical,
+    }
+}
+
+/// The body of `Notification::set_body_with_link`: with an anchor if the
+/// server advertises `body-hyperlinks`, or else with the raw URL, escaped if
+/// it advertises `body-markup`.
+fn link_body(
+    text: &str,
+    url: &str,
+    link_text: &str,
+    hyperlinks: bool,
+    markup: bool,
+) -> String {
+    if hyperlinks {
+        format!(
+            "{} <a href=\"{}\">{}</a>",
+            escape_markup(text),
+            escape_markup(url),
+            escape_markup(link_text)
+        )
+    } else {
+        let body = format!("{} {} ({})", text, link_text, url);
+        if markup { escape_markup(&body) } else { body }
+    }
+}
+
+/// The body of `Notification::digest`, as markup or as plain text.
+fn digest_body(items: &[&str], markup: bool) -> String {
+    let mut lines: Vec<String> = items
+        .iter()
+        .take(DIGEST_MAX_ITEMS)
+        .map(|item| {
+            if markup {
+                format!("\u{2022} {}", escape_markup(item))
+            } else {
+                (*item).to_owned()
+            }
+        })
+        .collect();
+    if items.len() > DIGEST_MAX_ITEMS {
+        let more = format!("and {} more", items.len() - DIGEST_MAX_ITEMS);
+        lines.push(if markup { format!("<i>{}</i>", more) } else { more });
+    }
+    lines.join("\n")
+}
+
+/// The timeout of `Notification::set_timeout_for_reading`, in milliseconds.
+fn reading_timeout_ms(summary: &str, body: &str, wpm: u32) -> u64 {
+    let words = summary.split_whitespace().count() +
+        strip_markup(body).split_whitespace().count();
+
+    let reading =
+        60_000u64.saturating_mul(words as u64) / u64::from(wpm.max(1));
+    READING_BASE_MS
+        .saturating_add(reading)
+        .clamp(READING_MIN_MS, READING_MAX_MS)
+}
+
+/// Shortens `text` to at most `max_chars` characters, ending in an ellipsis
+/// if it was cut.
+fn truncate_text(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some(_) if max_chars == 0 => String::new(),
+        Some(_) => {
+            let end = text
+                .char_indices()
+                .nth(max_chars - 1)
+                .map_or(text.len(), |(i, _)| i);
+            format!("{}\u{2026}", &text[..end])
+        }
+        None => text.to_owned(),
+    }
+}
+
+/// Breaks each line of `text` at whitespace so that it fits in `width`
+/// characters where possible.
+fn wrap_text(text: &str, width: usize) -> String {
+    let mut wrapped = String::with_capacity(text.len());
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 {
+            wrapped.push('\n');
+        }
+        let mut line_width = 0;
+        for word in line.split_whitespace() {
+            let word_width = word.chars().count();
+            if line_width > 0 {
+                if line_width + 1 + word_width > width {
+                    wrapped.push('\n');
+                    line_width = 0;
+                } else {
+                    wrapped.push(' ');
+                    line_width += 1;
+                }
             }
+            wrapped.push_str(word);
+            line_width += word_width;
         }
     }
+    wrapped
+}
+
+
+/// A snapshot of a notification, see `Notification::debug_state`.
+#[derive(Clone, Debug)]
+pub struct NotificationDebug {
+    /// The id assigned by the server, or `0` if not shown yet.
+    pub id: i32,
+    /// The summary text.
+    pub summary: Option<String>,
+    /// The body text.
+    pub body: Option<String>,
+    /// The icon theme icon name or filename.
+    pub icon: Option<String>,
+    /// The urgency set through this crate, if any.
+    pub urgency: Option<Urgency>,
+    /// The timeout set through this crate.
+    pub timeout: Timeout,
+    /// Whether the body is markup, see `Notification::set_markup_enabled`.
+    pub markup: bool,
+    /// The hints set through this crate, in the order they were first set.
+    pub hints: Vec<(String, glib::Variant)>,
+    /// The `(key, label)` pairs of the actions, in the order they were
+    /// added.
+    pub actions: Vec<(String, String)>,
+}
+
+
+/// How the server handled a notification, as reported by
+/// `Notification::show_tracked`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum ShowOutcome {
+    /// The notification was shown for the first time and got a new id.
+    New(i32),
+    /// The shown notification was replaced in place and kept its id.
+    Replaced(i32),
+    /// The shown notification was not replaced, the server assigned a new
+    /// id instead, e.g. because the old notification had been closed.
+    Reassigned {
+        /// The id before showing.
+        previous: i32,
+        /// The new id.
+        id: i32,
+    },
+    /// The server gave the notification the id of another notification
+    /// created on this thread, i.e. it merged the two.
+    Merged(i32),
+    /// The notification was not sent yet because a batch is in progress,
+    /// see `Notification::begin_batch`.
+    Deferred,
+    /// The notification was not sent and has no id, because it was dropped
+    /// by the limit set with `set_rate_limit()`, or with the `mock` feature
+    /// because `set_mock_show_result()` injected a success.
+    Dropped,
+}
+
+
+/// Identifies an action added with `Notification::add_action`, for
+/// `Notification::remove_action`. Handles are only meaningful for the
+/// notification that returned them.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct ActionHandle(u64);
+
+
+/// A shown `Notification` that is closed when the guard is dropped, see
+/// `Notification::sticky`.
+#[derive(Debug)]
+pub struct NotificationGuard(Notification);
+
+impl Deref for NotificationGuard {
+    type Target = Notification;
+
+    fn deref(&self) -> &Notification {
+        &self.0
+    }
+}
+
+impl Drop for NotificationGuard {
+    fn drop(&mut self) {
+        if is_initted() {
+            let _ = self.0.close();
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digest_body_plain() {
+        assert_eq!(digest_body(&["a & b", "c"], false), "a & b\nc");
+        assert_eq!(digest_body(&[], false), "");
+    }
+
+    #[test]
+    fn digest_body_markup() {
+        assert_eq!(
+            digest_body(&["a & b", "<c>"], true),
+            "\u{2022} a &amp; b\n\u{2022} &lt;c&gt;"
+        );
+    }
+
+    #[test]
+    fn digest_body_truncates_long_lists() {
+        let items = ["1", "2", "3", "4", "5", "6", "7"];
+        let plain = digest_body(&items, false);
+        assert_eq!(plain, "1\n2\n3\n4\n5\nand 2 more");
+        assert!(digest_body(&items, true).ends_with("\n<i>and 2 more</i>"));
+        assert!(!digest_body(&items[..5], false).contains("more"));
+    }
+
+    #[test]
+    fn reading_timeout_follows_formula() {
+        // 10 words at 200 wpm take 3 seconds, plus the base of 1 second.
+        let body = "one two three four five six seven eight";
+        assert_eq!(reading_timeout_ms("Summary text", body, 200), 4_000);
+        // Markup is not counted.
+        let body = "<b>one</b> two three four five six seven eight";
+        assert_eq!(reading_timeout_ms("Summary text", body, 200), 4_000);
+    }
+
+    #[test]
+    fn reading_timeout_is_clamped() {
+        assert_eq!(reading_timeout_ms("Hi", "", 200), READING_MIN_MS);
+        let long = "word ".repeat(1_000);
+        assert_eq!(reading_timeout_ms("", &long, 200), READING_MAX_MS);
+        assert_eq!(reading_timeout_ms("a b c", "", 0), READING_MAX_MS);
+    }
+
+    #[test]
+    fn truncate_text_keeps_short_text() {
+        assert_eq!(truncate_text("short", 5), "short");
+        assert_eq!(truncate_text("", 0), "");
+    }
+
+    #[test]
+    fn truncate_text_counts_chars_not_bytes() {
+        assert_eq!(truncate_text("summary", 4), "sum\u{2026}");
+        assert_eq!(truncate_text("ÄÖÜäöü", 3), "ÄÖ\u{2026}");
+        assert_eq!(truncate_text("日本語テキスト", 4), "日本語\u{2026}");
+        assert_eq!(truncate_text("🎉🎉🎉", 2), "🎉\u{2026}");
+    }
+
+    #[test]
+    fn truncate_text_handles_tiny_limits() {
+        assert_eq!(truncate_text("summary", 1), "\u{2026}");
+        assert_eq!(truncate_text("summary", 0), "");
+    }
+
+    #[test]
+    fn wrap_text_breaks_at_words() {
+        let text = "the quick brown fox";
+        assert_eq!(wrap_text(text, 9), "the quick\nbrown fox");
+        assert_eq!(wrap_text(text, 80), text);
+    }
+
+    #[test]
+    fn wrap_text_counts_chars_not_bytes() {
+        let text = "héllo wörld ñandú";
+        assert_eq!(wrap_text(text, 11), "héllo wörld\nñandú");
+        assert_eq!(wrap_text("日本語 テキスト", 3), "日本語\nテキスト");
+    }
+
+    #[test]
+    fn wrap_text_keeps_long_words_whole() {
+        assert_eq!(
+            wrap_text("a supercalifragilistic b", 5),
+            "a\nsupercalifragilistic\nb"
+        );
+        assert_eq!(wrap_text("naïveté", 3), "naïveté");
+    }
+
+    #[test]
+    fn wrap_text_keeps_line_breaks() {
+        let text = "one  two\n\nthree four";
+        assert_eq!(wrap_text(text, 9), "one two\n\nthree\nfour");
+    }
+
+    #[test]
+    fn link_body_uses_anchor_with_hyperlinks() {
+        assert_eq!(
+            link_body("See", "https://a.b/?x=1&y=2", "docs", true, true),
+            "See <a href=\"https://a.b/?x=1&amp;y=2\">docs</a>"
+        );
+        assert_eq!(
+            link_body("<b>", "u", "\"q\"", true, false),
+            "&lt;b&gt; <a href=\"u\">&quot;q&quot;</a>"
+        );
+    }
+
+    #[test]
+    fn link_body_falls_back_to_raw_url() {
+        assert_eq!(
+            link_body("Fish & chips", "https://a.b/", "menu", false, false),
+            "Fish & chips menu (https://a.b/)"
+        );
+        assert_eq!(
+            link_body("Fish & chips", "https://a.b/", "menu", false, true),
+            "Fish &amp; chips menu (https://a.b/)"
+        );
+    }
+
+    #[test]
+    fn next_urgency_raises_one_level() {
+        assert_eq!(next_urgency(Urgency::Low), Urgency::Normal);
+        assert_eq!(next_urgency(Urgency::Normal), Urgency::Critical);
+        assert_eq!(next_urgency(Urgency::Critical), Urgency::Critical);
+        assert_eq!(next_urgency(Urgency::__Unknown(7)), Urgency::Critical);
+    }
+
+    #[test]
+    fn check_update_rejects_nul_bytes() {
+        assert_eq!(
+            check_update("a\0b", None, None),
+            Err(String::from("Nul byte in summary"))
+        );
+        assert_eq!(
+            check_update("a", Some("b\0"), None),
+            Err(String::from("Nul byte in body"))
+        );
+        assert_eq!(
+            check_update("a", Some("b"), Some("\0")),
+            Err(String::from("Nul byte in icon"))
+        );
+    }
+
+    #[test]
+    fn check_update_accepts_empty_and_missing() {
+        assert_eq!(check_update("", Some(""), Some("")), Ok(()));
+        assert_eq!(check_update("a", None, None), Ok(()));
+        assert_eq!(check_update("a", Some("b"), None), Ok(()));
+    }
+
+    #[test]
+    fn rejected_update_names_lengths() {
+        assert_eq!(
+            rejected_update("sum", Some("héllo"), None),
+            "Invalid parameter passed (summary: 3 bytes, body: 6 bytes, \
+             icon: none)"
+        );
+        assert_eq!(
+            rejected_update("", None, Some("")),
+            "Invalid parameter passed (summary: 0 bytes, body: none, \
+             icon: 0 bytes)"
+        );
+    }
 }
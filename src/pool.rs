@@ -0,0 +1,116 @@
+use Notification;
+use Timeout;
+use default_hints;
+use default_icon;
+use glib::{ObjectExt, ToValue};
+use std::cell::RefCell;
+use std::ops::Deref;
+
+
+/// A pool of reusable `Notification` handles, for applications showing many
+/// short-lived notifications. Instead of creating and dropping a
+/// `NotifyNotification` for every notification, handles are returned to the
+/// pool when the `PooledNotification` is dropped and handed out again by
+/// `NotificationPool::get`.
+///
+/// Hints, actions, the timeout and the texts are reset when a handle is
+/// returned, and the defaults of `set_default_hints()` and
+/// `set_default_icon()` are applied again as for a new notification. Signal
+/// handlers such as those connected with `Notification::connect_closed` are
+/// not reset, so they should be disconnected before the `PooledNotification`
+/// is dropped.
+#[derive(Debug, Default)]
+pub struct NotificationPool {
+    free: RefCell<Vec<Notification>>,
+}
+
+impl NotificationPool {
+    /// Creates a new, empty `NotificationPool`.
+    ///
+    /// # Returns
+    ///
+    /// The new `NotificationPool`.
+    pub fn new() -> NotificationPool {
+        NotificationPool::default()
+    }
+
+    /// Gets a notification from the pool, or creates a new one if the pool
+    /// is empty. The notification has an empty summary and no body, icon,
+    /// hints or actions; it must be configured with `Notification::update`
+    /// and the other setters before it is shown. Showing it displays a new
+    /// notification rather than replacing the one previously shown with the
+    /// same handle.
+    ///
+    /// # Returns
+    ///
+    /// The notification, returned to the pool when dropped.
+    pub fn get(&self) -> PooledNotification<'_> {
+        let notification = self.free
+            .borrow_mut()
+            .pop()
+            .unwrap_or_else(|| Notification::new("", None, None));
+        PooledNotification {
+            pool: self,
+            notification: Some(notification),
+        }
+    }
+
+    /// Gets the number of notifications currently available in the pool.
+    ///
+    /// # Returns
+    ///
+    /// The number of idle notifications.
+    pub fn len(&self) -> usize {
+        self.free.borrow().len()
+    }
+
+    /// Whether no notifications are currently available in the pool.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the pool is empty, `false` otherwise.
+    pub fn is_empty(&self) -> bool {
+        self.free.borrow().is_empty()
+    }
+}
+
+
+/// A `Notification` borrowed from a `NotificationPool`, which it is reset
+/// and returned to when dropped.
+#[derive(Debug)]
+pub struct PooledNotification<'a> {
+    pool: &'a NotificationPool,
+    notification: Option<Notification>,
+}
+
+impl<'a> Deref for PooledNotification<'a> {
+    type Target = Notification;
+
+    fn deref(&self) -> &Notification {
+        self.notification.as_ref().unwrap()
+    }
+}
+
+impl<'a> Drop for PooledNotification<'a> {
+    fn drop(&mut self) {
+        if let Some(notification) = self.notification.take() {
+            // Clearing the hints also unsets the urgency and the category,
+            // which libnotify stores as hints.
+            notification.clear_hints();
+            notification.clear_actions();
+            notification.discard_batch();
+            notification.set_markup_enabled(false);
+            notification.set_timeout(Timeout::Default);
+            notification.set_app_name(None);
+            for (key, value) in default_hints() {
+                notification.set_hint(&key, Some(value));
+            }
+            let icon = default_icon();
+            if notification.update("", None, icon.as_deref()).is_ok() &&
+                notification.set_property("id", &0.to_value()).is_ok()
+            {
+                self.pool.free.borrow_mut().push(notification);
+            }
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use gio_ffi;
+use glib;
+use glib::translate::*;
+use glib_ffi;
+use gobject_ffi;
+use std::os::raw::c_char;
+use std::ptr;
+
+
+const SCREENSAVER: &[u8] = b"org.freedesktop.ScreenSaver\0";
+const SCREENSAVER_PATH: &[u8] = b"/org/freedesktop/ScreenSaver\0";
+const GET_ACTIVE: &[u8] = b"GetActive\0";
+/// How long to wait for the screensaver, in milliseconds.
+const SCREENSAVER_TIMEOUT: i32 = 500;
+
+
+/// Asks the `org.freedesktop.ScreenSaver` service on the session bus
+/// whether the screensaver is active, which is taken to mean that the user
+/// is away.
+///
+/// # Returns
+///
+/// Whether the screensaver is active, or `None` if there is no session bus
+/// or screensaver service.
+pub(crate) fn screensaver_active() -> Option<bool> {
+    unsafe {
+        let mut err: *mut glib_ffi::GError = ptr::null_mut();
+        let connection = gio_ffi::g_bus_get_sync(
+            gio_ffi::G_BUS_TYPE_SESSION,
+            ptr::null_mut(),
+            &mut err,
+        );
+        if connection.is_null() {
+            drop(glib::error::Error::wrap(err));
+            return None;
+        }
+
+        let reply = gio_ffi::g_dbus_connection_call_sync(
+            connection,
+            SCREENSAVER.as_ptr() as *const c_char,
+            SCREENSAVER_PATH.as_ptr() as *const c_char,
+            SCREENSAVER.as_ptr() as *const c_char,
+            GET_ACTIVE.as_ptr() as *const c_char,
+            ptr::null_mut(),
+            ptr::null(),
+            gio_ffi::G_DBUS_CALL_FLAGS_NO_AUTO_START,
+            SCREENSAVER_TIMEOUT,
+            ptr::null_mut(),
+            &mut err,
+        );
+        gobject_ffi::g_object_unref(connection as *mut gobject_ffi::GObject);
+        if reply.is_null() {
+            drop(glib::error::Error::wrap(err));
+            return None;
+        }
+
+        let reply: glib::Variant = from_glib_full(reply);
+        if reply.type_().to_str() != "(b)" {
+            return None;
+        }
+        let active: glib::Variant = from_glib_full(
+            glib_ffi::g_variant_get_child_value(reply.to_glib_none().0, 0),
+        );
+        active.get::<bool>()
+    }
+}
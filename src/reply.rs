@@ -0,0 +1,112 @@
+use gio_ffi;
+use glib;
+use glib::translate::*;
+use glib_ffi;
+use gobject_ffi;
+use std::cell::RefCell;
+use std::os::raw::c_char;
+use std::ptr;
+
+
+const NOTIFICATIONS: &[u8] = b"org.freedesktop.Notifications\0";
+const NOTIFICATIONS_PATH: &[u8] = b"/org/freedesktop/Notifications\0";
+const NOTIFICATION_REPLIED: &[u8] = b"NotificationReplied\0";
+
+
+/// A subscription to the `NotificationReplied` signal, see
+/// `subscribe_replies()`. Dropping it unsubscribes and frees the callback.
+pub(crate) struct ReplySubscription {
+    connection: *mut gio_ffi::GDBusConnection,
+    id: u32,
+}
+
+impl Drop for ReplySubscription {
+    fn drop(&mut self) {
+        unsafe {
+            gio_ffi::g_dbus_connection_signal_unsubscribe(
+                self.connection,
+                self.id,
+            );
+            gobject_ffi::g_object_unref(
+                self.connection as *mut gobject_ffi::GObject,
+            );
+        }
+    }
+}
+
+
+/// Subscribes to the `NotificationReplied` signal that servers supporting
+/// the `inline-reply` capability emit when the user sends an inline reply,
+/// and calls `f` with the server id of the notification and the text. The
+/// callbacks are dispatched by the thread-default main context of the
+/// calling thread.
+///
+/// # Returns
+///
+/// The subscription, or `None` if there is no session bus.
+pub(crate) fn subscribe_replies<F: FnMut(u32, &str) + 'static>(
+    f: F,
+) -> Option<ReplySubscription> {
+    unsafe {
+        let mut err: *mut glib_ffi::GError = ptr::null_mut();
+        let connection = gio_ffi::g_bus_get_sync(
+            gio_ffi::G_BUS_TYPE_SESSION,
+            ptr::null_mut(),
+            &mut err,
+        );
+        if connection.is_null() {
+            drop(glib::error::Error::wrap(err));
+            return None;
+        }
+
+        let f: Box<ReplyCallback> = Box::new(RefCell::new(Box::new(f)));
+        let id = gio_ffi::g_dbus_connection_signal_subscribe(
+            connection,
+            NOTIFICATIONS.as_ptr() as *const c_char,
+            NOTIFICATIONS.as_ptr() as *const c_char,
+            NOTIFICATION_REPLIED.as_ptr() as *const c_char,
+            NOTIFICATIONS_PATH.as_ptr() as *const c_char,
+            ptr::null(),
+            gio_ffi::G_DBUS_SIGNAL_FLAGS_NONE,
+            Some(replied_trampoline),
+            Box::into_raw(f) as glib_ffi::gpointer,
+            Some(destroy_reply),
+        );
+        Some(ReplySubscription { connection, id })
+    }
+}
+
+
+type ReplyCallback = RefCell<Box<dyn FnMut(u32, &str) + 'static>>;
+
+unsafe extern "C" fn replied_trampoline(
+    _connection: *mut gio_ffi::GDBusConnection,
+    _sender: *const c_char,
+    _path: *const c_char,
+    _interface: *const c_char,
+    _signal: *const c_char,
+    parameters: *mut glib_ffi::GVariant,
+    f: glib_ffi::gpointer,
+) {
+    let _guard = glib::CallbackGuard::new();
+    let parameters: glib::Variant = from_glib_none(parameters);
+    if parameters.type_().to_str() != "(us)" {
+        return;
+    }
+    let child = |index| -> glib::Variant {
+        from_glib_full(glib_ffi::g_variant_get_child_value(
+            parameters.to_glib_none().0,
+            index,
+        ))
+    };
+    let (id, text) = (child(0), child(1));
+    if let (Some(id), Some(text)) = (id.get::<u32>(), text.get_str()) {
+        let f = &*(f as *const ReplyCallback);
+        (f.borrow_mut())(id, text)
+    }
+}
+
+unsafe extern "C" fn destroy_reply(f: glib_ffi::gpointer) {
+    let _guard = glib::CallbackGuard::new();
+    drop(Box::from_raw(f as *mut ReplyCallback));
+}
@@ -0,0 +1,98 @@
+use get_server_info;
+
+
+/// A notification server, as identified by `detect_server()`.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum KnownServer {
+    /// [dunst](https://dunst-project.org/)
+    Dunst,
+    /// [mako](https://github.com/emersion/mako)
+    Mako,
+    /// The notifications built into GNOME Shell.
+    GnomeShell,
+    /// The notifications built into KDE Plasma.
+    Plasma,
+    /// The Xfce notification daemon.
+    Xfce4Notifyd,
+    /// Ubuntu's notify-osd.
+    NotifyOsd,
+    /// Any other server, with the name it reported. The name is empty if
+    /// the server could not be queried.
+    Unknown(String),
+}
+
+impl KnownServer {
+    fn from_info(name: &str, vendor: &str) -> KnownServer {
+        match name {
+            "dunst" => KnownServer::Dunst,
+            "mako" => KnownServer::Mako,
+            "gnome-shell" => KnownServer::GnomeShell,
+            "Plasma" => KnownServer::Plasma,
+            "Xfce Notify Daemon" | "xfce4-notifyd" => {
+                KnownServer::Xfce4Notifyd
+            }
+            "notify-osd" => KnownServer::NotifyOsd,
+            _ if vendor == "KDE" => KnownServer::Plasma,
+            _ => KnownServer::Unknown(name.to_owned()),
+        }
+    }
+}
+
+
+/// Identifies the running notification server, so that server-specific
+/// quirks can be applied.
+///
+/// This is a heuristic based on the name and vendor the server reports via
+/// `get_server_info()`. Servers are free to report anything there, and
+/// forks or new versions may report names that are not recognized.
+///
+/// # Returns
+///
+/// The server, or `KnownServer::Unknown` with its reported name.
+pub fn detect_server() -> KnownServer {
+    match get_server_info() {
+        Some((name, vendor, _, _)) => KnownServer::from_info(&name, &vendor),
+        None => KnownServer::Unknown(String::new()),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_info_recognizes_known_servers() {
+        let servers = [
+            ("dunst", "knopwob", KnownServer::Dunst),
+            ("mako", "emersion", KnownServer::Mako),
+            ("gnome-shell", "GNOME", KnownServer::GnomeShell),
+            ("Plasma", "KDE", KnownServer::Plasma),
+            ("Plasma", "", KnownServer::Plasma),
+            ("Xfce Notify Daemon", "Xfce", KnownServer::Xfce4Notifyd),
+            ("xfce4-notifyd", "Xfce", KnownServer::Xfce4Notifyd),
+            ("notify-osd", "Canonical Ltd", KnownServer::NotifyOsd),
+        ];
+        for &(name, vendor, ref server) in &servers {
+            assert_eq!(KnownServer::from_info(name, vendor), *server);
+        }
+    }
+
+    #[test]
+    fn from_info_detects_kde_by_vendor() {
+        assert_eq!(
+            KnownServer::from_info("knotify", "KDE"),
+            KnownServer::Plasma
+        );
+    }
+
+    #[test]
+    fn from_info_falls_back_to_unknown() {
+        for &name in &["swaync", "Dunst", "", "gnome shell"] {
+            assert_eq!(
+                KnownServer::from_info(name, "Vendor"),
+                KnownServer::Unknown(name.to_owned())
+            );
+        }
+    }
+}
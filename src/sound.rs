@@ -0,0 +1,142 @@
+use std::convert::Infallible;
+use std::fmt;
+use std::str::FromStr;
+
+
+/// Common sound names of the freedesktop sound theme specification, for
+/// `Notification::set_sound`. Sound names round-trip through their string
+/// representation with `Display` and `FromStr`, where strings that are not
+/// listed here become `SoundName::Custom`.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum SoundName {
+    /// An alarm or timer went off.
+    AlarmClockElapsed,
+    /// The battery is nearly empty.
+    BatteryCaution,
+    /// The battery is low.
+    BatteryLow,
+    /// The generic terminal or window bell.
+    Bell,
+    /// A photo was taken.
+    CameraShutter,
+    /// A long-running task completed.
+    Complete,
+    /// A device was added to the system.
+    DeviceAdded,
+    /// A device was removed from the system.
+    DeviceRemoved,
+    /// An error dialog was shown.
+    DialogError,
+    /// An informational dialog was shown.
+    DialogInformation,
+    /// A warning dialog was shown.
+    DialogWarning,
+    /// A new e-mail arrived.
+    MessageNewEmail,
+    /// A new instant message arrived.
+    MessageNewInstant,
+    /// The network connection was established.
+    NetworkConnectivityEstablished,
+    /// The network connection was lost.
+    NetworkConnectivityLost,
+    /// The power cable was plugged in.
+    PowerPlug,
+    /// The power cable was unplugged.
+    PowerUnplug,
+    /// A screenshot was taken.
+    ScreenCapture,
+    /// Suspending the system failed.
+    SuspendError,
+    /// The trash was emptied.
+    TrashEmpty,
+    /// Any other sound name of the sound theme.
+    Custom(String),
+}
+
+impl SoundName {
+    /// Gets the sound name, as sent to the notification server.
+    ///
+    /// # Returns
+    ///
+    /// The sound name, e.g. `"message-new-instant"`.
+    pub fn as_str(&self) -> &str {
+        match *self {
+            SoundName::AlarmClockElapsed => "alarm-clock-elapsed",
+            SoundName::BatteryCaution => "battery-caution",
+            SoundName::BatteryLow => "battery-low",
+            SoundName::Bell => "bell",
+            SoundName::CameraShutter => "camera-shutter",
+            SoundName::Complete => "complete",
+            SoundName::DeviceAdded => "device-added",
+            SoundName::DeviceRemoved => "device-removed",
+            SoundName::DialogError => "dialog-error",
+            SoundName::DialogInformation => "dialog-information",
+            SoundName::DialogWarning => "dialog-warning",
+            SoundName::MessageNewEmail => "message-new-email",
+            SoundName::MessageNewInstant => "message-new-instant",
+            SoundName::NetworkConnectivityEstablished => {
+                "network-connectivity-established"
+            }
+            SoundName::NetworkConnectivityLost => "network-connectivity-lost",
+            SoundName::PowerPlug => "power-plug",
+            SoundName::PowerUnplug => "power-unplug",
+            SoundName::ScreenCapture => "screen-capture",
+            SoundName::SuspendError => "suspend-error",
+            SoundName::TrashEmpty => "trash-empty",
+            SoundName::Custom(ref name) => name,
+        }
+    }
+}
+
+impl fmt::Display for SoundName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SoundName {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<SoundName, Infallible> {
+        Ok(match s {
+            "alarm-clock-elapsed" => SoundName::AlarmClockElapsed,
+            "battery-caution" => SoundName::BatteryCaution,
+            "battery-low" => SoundName::BatteryLow,
+            "bell" => SoundName::Bell,
+            "camera-shutter" => SoundName::CameraShutter,
+            "complete" => SoundName::Complete,
+            "device-added" => SoundName::DeviceAdded,
+            "device-removed" => SoundName::DeviceRemoved,
+            "dialog-error" => SoundName::DialogError,
+            "dialog-information" => SoundName::DialogInformation,
+            "dialog-warning" => SoundName::DialogWarning,
+            "message-new-email" => SoundName::MessageNewEmail,
+            "message-new-instant" => SoundName::MessageNewInstant,
+            "network-connectivity-established" => {
+                SoundName::NetworkConnectivityEstablished
+            }
+            "network-connectivity-lost" => SoundName::NetworkConnectivityLost,
+            "power-plug" => SoundName::PowerPlug,
+            "power-unplug" => SoundName::PowerUnplug,
+            "screen-capture" => SoundName::ScreenCapture,
+            "suspend-error" => SoundName::SuspendError,
+            "trash-empty" => SoundName::TrashEmpty,
+            name => SoundName::Custom(name.to_owned()),
+        })
+    }
+}
+
+impl<'a> From<&'a str> for SoundName {
+    fn from(name: &'a str) -> SoundName {
+        match name.parse() {
+            Ok(name) => name,
+            Err(never) => match never {},
+        }
+    }
+}
+
+impl From<String> for SoundName {
+    fn from(name: String) -> SoundName {
+        SoundName::from(name.as_str())
+    }
+}
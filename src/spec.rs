@@ -0,0 +1,162 @@
+use Notification;
+use Urgency;
+use glib;
+use get_server_caps;
+use notification::check_nul;
+use strip_markup;
+
+
+/// A problem found by `NotificationSpec::validate`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum ValidationIssue {
+    /// The summary is empty or consists only of whitespace.
+    EmptySummary,
+    /// The named field contains a nul byte and cannot be sent.
+    NulByte(&'static str),
+    /// A body is set, but the server does not advertise the `body`
+    /// capability and will not display it.
+    BodyNotSupported,
+    /// The body contains markup, but the server does not advertise the
+    /// `body-markup` capability and will display the raw tags.
+    MarkupNotSupported,
+}
+
+
+/// A description of a notification, independent of libnotify. Unlike
+/// `Notification`, it can be created before libnotify is initialized and
+/// sent to other threads.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NotificationSpec {
+    /// The required summary text.
+    pub summary: String,
+    /// The optional body text.
+    pub body: Option<String>,
+    /// The optional icon theme icon name or filename.
+    pub icon: Option<String>,
+    /// The urgency level, or `None` for the server default.
+    pub urgency: Option<Urgency>,
+    /// The timeout in milliseconds, or `None` for `EXPIRES_DEFAULT`.
+    pub timeout: Option<i32>,
+    /// The category, or `None` for no category.
+    pub category: Option<String>,
+    /// Additional hints, set in order.
+    pub hints: Vec<(String, glib::Variant)>,
+}
+
+impl NotificationSpec {
+    /// Creates a new `NotificationSpec` with the given summary and everything
+    /// else unset.
+    /// ## `summary`
+    /// The required summary text.
+    ///
+    /// # Returns
+    ///
+    /// The new `NotificationSpec`.
+    pub fn new(summary: &str) -> NotificationSpec {
+        NotificationSpec {
+            summary: summary.to_owned(),
+            ..NotificationSpec::default()
+        }
+    }
+
+    /// Checks the spec against the capabilities of the notification server,
+    /// without showing anything. This allows adapting the content to the
+    /// server before showing it.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if no problems were found, or `Err(issues)` listing them.
+    pub fn validate(&self) -> Result<(), Vec<ValidationIssue>> {
+        let caps = get_server_caps();
+        let has_cap = |cap: &str| caps.iter().any(|c| c == cap);
+        let mut issues = Vec::new();
+
+        if self.summary.trim().is_empty() {
+            issues.push(ValidationIssue::EmptySummary);
+        }
+        let fields = [
+            ("summary", Some(&self.summary)),
+            ("body", self.body.as_ref()),
+            ("icon", self.icon.as_ref()),
+            ("category", self.category.as_ref()),
+        ];
+        for &(name, value) in &fields {
+            if value.is_some_and(|value| value.contains('\0')) {
+                issues.push(ValidationIssue::NulByte(name));
+            }
+        }
+        if self.hints.iter().any(|(key, _)| key.contains('\0')) {
+            issues.push(ValidationIssue::NulByte("hint key"));
+        }
+        if let Some(ref body) = self.body {
+            if !has_cap("body") {
+                issues.push(ValidationIssue::BodyNotSupported);
+            } else if !has_cap("body-markup") && strip_markup(body) != *body {
+                issues.push(ValidationIssue::MarkupNotSupported);
+            }
+        }
+
+        if issues.is_empty() { Ok(()) } else { Err(issues) }
+    }
+
+    /// Fails if any of the strings cannot be passed to libnotify.
+    pub(crate) fn check(&self) -> Result<(), String> {
+        check_nul("summary", &self.summary)?;
+        check_nul("body", self.body.as_deref().unwrap_or(""))?;
+        check_nul("icon", self.icon.as_deref().unwrap_or(""))?;
+        check_nul("category", self.category.as_deref().unwrap_or(""))?;
+        for (key, _) in &self.hints {
+            check_nul("hint key", key)?;
+        }
+        Ok(())
+    }
+
+    /// Creates a `Notification` from the spec and applies all of its fields,
+    /// without showing it. This fails on the first string that cannot be
+    /// passed to libnotify, see `NotificationSpec::validate` to find all
+    /// problems at once.
+    ///
+    /// # Returns
+    ///
+    /// The new `Notification`, or `Err(str)` if a string contains a nul byte
+    pub fn build(&self) -> Result<Notification, String> {
+        self.check()?;
+
+        let notification = Notification::new(
+            &self.summary,
+            self.body.as_deref(),
+            self.icon.as_deref(),
+        );
+        if let Some(urgency) = self.urgency {
+            notification.set_urgency(urgency);
+        }
+        if let Some(timeout) = self.timeout {
+            notification.set_timeout(timeout);
+        }
+        if let Some(ref category) = self.category {
+            notification.set_category(category.as_str());
+        }
+        for (key, value) in &self.hints {
+            notification.set_hint(key, Some(value.clone()));
+        }
+        Ok(notification)
+    }
+}
+
+
+/// Creates a `NotificationSpec` from a `(summary, body, icon)` tuple, with
+/// everything else unset. Use `NotificationSpec::build` to turn it into a
+/// `Notification`.
+impl<'a, 'b, 'c> From<(&'a str, Option<&'b str>, Option<&'c str>)>
+    for NotificationSpec {
+    fn from(
+        (summary, body, icon): (&'a str, Option<&'b str>, Option<&'c str>),
+    ) -> NotificationSpec {
+        NotificationSpec {
+            summary: summary.to_owned(),
+            body: body.map(str::to_owned),
+            icon: icon.map(str::to_owned),
+            ..NotificationSpec::default()
+        }
+    }
+}
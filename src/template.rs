@@ -0,0 +1,195 @@
+use escape_markup;
+use std::collections::HashMap;
+
+
+/// A text with `{name}` placeholders, e.g. `"{user} sent you a message"`,
+/// for notifications that are shown repeatedly with different values, see
+/// `Notification::show_template`. `{{` and `}}` stand for literal braces.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Template {
+    text: String,
+    strict: bool,
+}
+
+impl Template {
+    /// Creates a new `Template`.
+    /// ## `text`
+    /// The text with placeholders.
+    ///
+    /// # Returns
+    ///
+    /// The new `Template`.
+    pub fn new(text: &str) -> Template {
+        Template {
+            text: text.to_owned(),
+            strict: false,
+        }
+    }
+
+    /// Sets whether `Notification::show_template` fails if a placeholder
+    /// has no value, rather than showing the placeholder as it is, which is
+    /// the default.
+    /// ## `strict`
+    /// Whether missing values are an error.
+    ///
+    /// # Returns
+    ///
+    /// The changed `Template`.
+    pub fn strict(mut self, strict: bool) -> Template {
+        self.strict = strict;
+        self
+    }
+
+    /// Replaces the placeholders with the values in `vars`. Placeholders
+    /// without a value are kept as they are.
+    /// ## `vars`
+    /// The values by placeholder name.
+    ///
+    /// # Returns
+    ///
+    /// The rendered text.
+    pub fn render(&self, vars: &HashMap<&str, &str>) -> String {
+        self.substitute(vars, false)
+            .unwrap_or_else(|_| unreachable!("missing values are kept"))
+    }
+
+    /// Like `Template::render`, but fails if a placeholder has no value.
+    /// ## `vars`
+    /// The values by placeholder name.
+    ///
+    /// # Returns
+    ///
+    /// The rendered text, or `Err(str)` naming every placeholder without a
+    /// value
+    pub fn try_render(
+        &self,
+        vars: &HashMap<&str, &str>,
+    ) -> Result<String, String> {
+        self.substitute(vars, true)
+    }
+
+    /// Renders the template for `Notification::show_template`, escaping the
+    /// values with `escape_markup` if the template contains markup, and
+    /// failing on missing values if the template is strict.
+    pub(crate) fn render_for_show(
+        &self,
+        vars: &HashMap<&str, &str>,
+        markup: bool,
+    ) -> Result<String, String> {
+        if !markup {
+            return self.substitute(vars, self.strict);
+        }
+        let escaped: HashMap<&str, String> = vars
+            .iter()
+            .map(|(&name, &value)| (name, escape_markup(value)))
+            .collect();
+        let vars = escaped
+            .iter()
+            .map(|(&name, value)| (name, value.as_str()))
+            .collect();
+        self.substitute(&vars, self.strict)
+    }
+
+    fn substitute(
+        &self,
+        vars: &HashMap<&str, &str>,
+        strict: bool,
+    ) -> Result<String, String> {
+        let mut out = String::with_capacity(self.text.len());
+        let mut missing = Vec::new();
+        let mut rest = self.text.as_str();
+
+        while let Some(i) = rest.find(['{', '}']) {
+            out.push_str(&rest[..i]);
+            rest = &rest[i..];
+            if rest.starts_with("{{") || rest.starts_with("}}") {
+                out.push_str(&rest[..1]);
+                rest = &rest[2..];
+            } else if let Some(len) =
+                rest.strip_prefix('{').and_then(|name| name.find('}'))
+            {
+                let name = &rest[1..len + 1];
+                match vars.get(name) {
+                    Some(value) => out.push_str(value),
+                    None if strict => missing.push(format!("{{{}}}", name)),
+                    None => out.push_str(&rest[..len + 2]),
+                }
+                rest = &rest[len + 2..];
+            } else {
+                out.push_str(&rest[..1]);
+                rest = &rest[1..];
+            }
+        }
+        out.push_str(rest);
+        if missing.is_empty() {
+            Ok(out)
+        } else {
+            Err(format!("No value for {}", missing.join(", ")))
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars() -> HashMap<&'static str, &'static str> {
+        let mut vars = HashMap::new();
+        vars.insert("user", "Ann & Bob");
+        vars.insert("count", "3");
+        vars
+    }
+
+    #[test]
+    fn render_substitutes_values() {
+        let template = Template::new("{user} sent you {count} messages");
+        assert_eq!(
+            template.render(&vars()),
+            "Ann & Bob sent you 3 messages"
+        );
+    }
+
+    #[test]
+    fn render_keeps_missing_placeholders_and_escapes() {
+        let template = Template::new("{{{user}}} {missing} {unclosed");
+        assert_eq!(
+            template.render(&vars()),
+            "{Ann & Bob} {missing} {unclosed"
+        );
+    }
+
+    #[test]
+    fn try_render_names_missing_placeholders() {
+        let template = Template::new("{user}: {a} {b}");
+        assert_eq!(
+            template.try_render(&vars()),
+            Err(String::from("No value for {a}, {b}"))
+        );
+        assert!(Template::new("{user}").try_render(&vars()).is_ok());
+    }
+
+    #[test]
+    fn render_for_show_escapes_markup() {
+        let template = Template::new("<b>{user}</b>");
+        assert_eq!(
+            template.render_for_show(&vars(), true),
+            Ok(String::from("<b>Ann &amp; Bob</b>"))
+        );
+        assert_eq!(
+            template.render_for_show(&vars(), false),
+            Ok(String::from("<b>Ann & Bob</b>"))
+        );
+    }
+
+    #[test]
+    fn render_for_show_honors_strict() {
+        let template = Template::new("{user} {missing}");
+        assert!(template.render_for_show(&vars(), false).is_ok());
+        let template = template.strict(true);
+        assert_eq!(
+            template.render_for_show(&vars(), false),
+            Err(String::from("No value for {missing}"))
+        );
+    }
+}
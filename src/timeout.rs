@@ -0,0 +1,42 @@
+use ffi;
+
+
+/// The expiration timeout of a notification.
+///
+/// Plain `i32` milliseconds convert into a `Timeout`, where the libnotify
+/// sentinels `EXPIRES_DEFAULT` (`-1`) and `EXPIRES_NEVER` (`0`) map to
+/// `Timeout::Default` and `Timeout::Never`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum Timeout {
+    /// Leave the expiration to the notification server. This is the
+    /// default for new notifications and usually the best choice, since it
+    /// respects the user's preferences.
+    #[default]
+    Default,
+    /// Never expire the notification, it stays until it is dismissed or
+    /// closed.
+    Never,
+    /// Expire the notification after the given number of milliseconds.
+    /// Servers may ignore this.
+    Milliseconds(i32),
+}
+
+impl From<i32> for Timeout {
+    fn from(timeout: i32) -> Timeout {
+        match timeout {
+            ffi::NOTIFY_EXPIRES_DEFAULT => Timeout::Default,
+            ffi::NOTIFY_EXPIRES_NEVER => Timeout::Never,
+            timeout => Timeout::Milliseconds(timeout),
+        }
+    }
+}
+
+impl From<Timeout> for i32 {
+    fn from(timeout: Timeout) -> i32 {
+        match timeout {
+            Timeout::Default => ffi::NOTIFY_EXPIRES_DEFAULT,
+            Timeout::Never => ffi::NOTIFY_EXPIRES_NEVER,
+            Timeout::Milliseconds(timeout) => timeout,
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use gio_ffi;
+use glib;
+use glib_ffi;
+use std::cell::RefCell;
+use std::os::raw::c_char;
+
+
+const NOTIFICATIONS_NAME: &[u8] = b"org.freedesktop.Notifications\0";
+
+
+/// A subscription created by `watch_server()`. Dropping it stops watching
+/// and frees the callback.
+#[must_use = "the server is only watched while the ServerWatch is alive"]
+#[derive(Debug)]
+pub struct ServerWatch(u32);
+
+impl Drop for ServerWatch {
+    fn drop(&mut self) {
+        unsafe {
+            gio_ffi::g_bus_unwatch_name(self.0);
+        }
+    }
+}
+
+
+/// Watches the `org.freedesktop.Notifications` name on the session bus and
+/// calls `f` with `true` when a notification server appears and with
+/// `false` when it disappears, e.g. to show important notifications again
+/// after the server was restarted.
+///
+/// The callbacks are dispatched by the thread-default main context of the
+/// calling thread, so a main loop must be running there. The first call,
+/// reporting the current state, happens once that main loop runs, not
+/// before `watch_server` returns. This does not require libnotify to be
+/// initialized. Servers that are started by D-Bus activation are reported
+/// as gone until something activates them, such as `Notification::show`.
+/// ## `f`
+/// The callback.
+///
+/// # Returns
+///
+/// The subscription, which stops watching when dropped.
+pub fn watch_server<F: FnMut(bool) + 'static>(f: F) -> ServerWatch {
+    unsafe {
+        let f: Box<WatchCallback> = Box::new(RefCell::new(Box::new(f)));
+        let id = gio_ffi::g_bus_watch_name(
+            gio_ffi::G_BUS_TYPE_SESSION,
+            NOTIFICATIONS_NAME.as_ptr() as *const c_char,
+            gio_ffi::GBusNameWatcherFlags::empty(),
+            Some(appeared_trampoline),
+            Some(vanished_trampoline),
+            Box::into_raw(f) as glib_ffi::gpointer,
+            Some(destroy_watch),
+        );
+        ServerWatch(id)
+    }
+}
+
+
+type WatchCallback = RefCell<Box<dyn FnMut(bool) + 'static>>;
+
+unsafe extern "C" fn appeared_trampoline(
+    _connection: *mut gio_ffi::GDBusConnection,
+    _name: *const c_char,
+    _owner: *const c_char,
+    f: glib_ffi::gpointer,
+) {
+    let _guard = glib::CallbackGuard::new();
+    let f = &*(f as *const WatchCallback);
+    (f.borrow_mut())(true)
+}
+
+unsafe extern "C" fn vanished_trampoline(
+    _connection: *mut gio_ffi::GDBusConnection,
+    _name: *const c_char,
+    f: glib_ffi::gpointer,
+) {
+    let _guard = glib::CallbackGuard::new();
+    let f = &*(f as *const WatchCallback);
+    (f.borrow_mut())(false)
+}
+
+unsafe extern "C" fn destroy_watch(f: glib_ffi::gpointer) {
+    let _guard = glib::CallbackGuard::new();
+    drop(Box::from_raw(f as *mut WatchCallback));
+}